@@ -1 +0,0 @@
-pub mod grpc_client;
\ No newline at end of file
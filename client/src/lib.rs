@@ -0,0 +1,109 @@
+#![allow(clippy::module_inception)]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::scraper::filter::CounterFilter;
+use collector_protocol::collector::collector::CollectorMetrics;
+use collector_protocol::sender::sender::{Compression, Protocol};
+use serde::{Deserialize, Serialize};
+pub mod scraper;
+pub mod reloader;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Config{
+    pub address: String,
+    pub namespace: Option<String>,
+    pub labels: Option<HashMap<String, String>>,
+    pub counters: Vec<Counter>,
+    pub interval: u64,
+    // directory the counter paths are looked up in instead of /, e.g. a
+    // copy of /sys/class/infiniband for testing
+    pub root: Option<String>,
+    // how metrics are sent to the collector, older collectors are sent
+    // uncompressed with the newest protocol they serve, see Protocol
+    #[serde(default)]
+    pub compression: Compression,
+    #[serde(default)]
+    pub protocol: Protocol,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Counter{
+    pub paths: Vec<String>,
+    pub labels: Option<HashMap<String, String>>,
+    pub rate_keys: Option<Vec<String>>,
+    // file names to export, all files are exported if neither include nor include_regex is set
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub include_regex: Option<Vec<String>>,
+    pub exclude_regex: Option<Vec<String>>,
+    // file name -> exported metric name
+    pub rename: Option<HashMap<String, String>>,
+    // file name -> factor the raw value is multiplied with, e.g. 4 for port_rcv_data
+    pub scale: Option<HashMap<String, f64>>,
+}
+
+pub fn read_config(path: &str) -> anyhow::Result<Config>{
+    let config = std::fs::read_to_string(path)?;
+    let config: Config = serde_yaml::from_str(&config)?;
+    Ok(config)
+}
+
+// a counter path below the root directory
+pub fn counter_path(root: Option<&str>, path: &str) -> PathBuf{
+    match root{
+        Some(root) => Path::new(root).join(path.trim_start_matches('/')),
+        None => PathBuf::from(path),
+    }
+}
+
+pub fn get_metrics_metadata(counter: Counter, global_labels: HashMap<String, String>, namespace: Option<String>, root: Option<&str>) -> anyhow::Result<CollectorMetrics>{
+    let mut metrics = HashMap::new();
+
+    let mut labels = if let Some(counter_labels) = &counter.labels{
+        counter_labels.clone()
+    } else {
+        HashMap::new()
+    };
+
+    labels.extend(global_labels.clone());
+    let filter = CounterFilter::new(&counter)?;
+    for path in &counter.paths{
+        let files = match std::fs::read_dir(counter_path(root, path)){
+            Ok(files) => files,
+            Err(_e) => {
+                continue;
+            }
+        };
+        for file in files{
+            let file = match file{
+                Ok(file) => file,
+                Err(_e) => {
+                    continue;
+                }
+            };
+            let path = file.path();
+            if !path.is_file(){
+                continue;
+            }
+            let key = file.file_name().into_string().map_err(|_| anyhow::anyhow!("Invalid file name"))?;
+            if !filter.matches(&key){
+                continue;
+            }
+            let name = filter.name(&key);
+            metrics.insert(name.clone(), 0);
+            if let Some(rate_keys) = &counter.rate_keys{
+                for rate_key in rate_keys{
+                    if rate_key == &key{
+                        metrics.insert(format!("{}_rate", name), 0);
+                    }
+                }
+            }
+        }
+    }
+    Ok(CollectorMetrics{
+        labels,
+        metrics,
+        namespace,
+    })
+
+}
@@ -0,0 +1 @@
+pub mod reloader;
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::{collections::HashMap, time::SystemTime};
+use log::{error, info, warn};
+use tokio::signal::unix::{signal, SignalKind};
+use collector_protocol::collector::collector::CollectorMetrics;
+use collector_protocol::sender::sender::Client;
+use crate::{
+    get_metrics_metadata, read_config,
+    scraper::{filter::CounterFilter, scraper::ScraperClient}, Config, Counter,
+};
+
+// how often the config file modification time is checked, in seconds
+const CHECK_INTERVAL: u64 = 5;
+
+pub struct Reloader{
+    path: String,
+    config: Config,
+    global_labels: HashMap<String, String>,
+    client: Client,
+    scraper_client: ScraperClient,
+    // the counters whose metrics are registered with the collector with the
+    // registered metrics, differs from the counters of the config while a
+    // failed reload is retried. The metrics are unregistered as registered,
+    // the files of a removed counter are usually gone.
+    registered: Vec<(Counter, CollectorMetrics)>,
+}
+
+impl Reloader{
+    pub fn new(path: String, config: Config, global_labels: HashMap<String, String>, client: Client, scraper_client: ScraperClient) -> Reloader{
+        Reloader{
+            path,
+            registered: Vec::new(),
+            config,
+            global_labels,
+            client,
+            scraper_client,
+        }
+    }
+
+    // registers the metrics of the counters of the config
+    pub async fn register(&mut self) -> anyhow::Result<()>{
+        for counter in self.config.counters.clone(){
+            if self.is_registered(&counter){
+                continue;
+            }
+            let reg_metrics = get_metrics_metadata(counter.clone(), self.global_labels.clone(), self.config.namespace.clone(), self.config.root.as_deref())?;
+            self.client.register_metrics(reg_metrics.clone()).await?;
+            self.registered.push((counter, reg_metrics));
+        }
+        Ok(())
+    }
+
+    fn is_registered(&self, counter: &Counter) -> bool{
+        self.registered.iter().any(|(c, _)| c == counter)
+    }
+
+    pub async fn run(mut self) -> anyhow::Result<()>{
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL));
+        let mut modified = modified_time(&self.path);
+        let mut retry = false;
+        info!("Watching config file {}", self.path);
+        loop{
+            tokio::select! {
+                _ = hangup.recv() => {
+                    info!("Received SIGHUP, reloading config");
+                },
+                _ = interval.tick() => {
+                    let current = modified_time(&self.path);
+                    if current == modified && !retry{
+                        continue;
+                    }
+                    modified = current;
+                    info!("Config file changed or last reload failed, reloading config");
+                }
+            }
+            retry = false;
+            if let Err(e) = self.reload().await{
+                error!("Failed to reload config, retrying in {}s: {}", CHECK_INTERVAL, e);
+                retry = true;
+            }
+        }
+    }
+
+    // applies the counter changes of the config file
+    pub async fn reload(&mut self) -> anyhow::Result<()>{
+        let config = read_config(&self.path)?;
+        if config.address != self.config.address
+            || config.namespace != self.config.namespace
+            || config.labels != self.config.labels
+            || config.interval != self.config.interval
+            || config.root != self.config.root
+            || config.compression != self.config.compression
+            || config.protocol != self.config.protocol{
+            warn!("Only counters are reloaded, changes to address, namespace, labels, interval, root, compression and protocol require a restart");
+        }
+        for counter in &config.counters{
+            CounterFilter::new(counter)?;
+        }
+        let added: Vec<Counter> = config.counters.iter().filter(|c| !self.is_registered(c)).cloned().collect();
+        let removed: Vec<(Counter, CollectorMetrics)> = self.registered.iter().filter(|(c, _)| !config.counters.contains(c)).cloned().collect();
+        if added.is_empty() && removed.is_empty() && config.counters == self.config.counters{
+            info!("No counter changes");
+            return Ok(());
+        }
+        info!("Counters added: {}, removed: {}", added.len(), removed.len());
+        // the removed counters are no longer scraped before they are
+        // unregistered, the added ones are registered before they are
+        // scraped. Every step is recorded, so a failed reload is retried
+        // from where it failed.
+        let kept: Vec<Counter> = config.counters.iter().filter(|c| self.is_registered(c)).cloned().collect();
+        self.scraper_client.update_counters(kept).await?;
+        for (counter, unreg_metrics) in removed{
+            self.client.unregister_metrics(unreg_metrics).await?;
+            self.registered.retain(|(c, _)| *c != counter);
+        }
+        for counter in added{
+            let reg_metrics = get_metrics_metadata(counter.clone(), self.global_labels.clone(), self.config.namespace.clone(), self.config.root.as_deref())?;
+            self.client.register_metrics(reg_metrics.clone()).await?;
+            self.registered.push((counter, reg_metrics));
+        }
+        self.scraper_client.update_counters(config.counters.clone()).await?;
+        self.config.counters = config.counters;
+        Ok(())
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime>{
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
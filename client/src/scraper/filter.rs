@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use collector_protocol::names::names::metric_name;
+use regex::Regex;
+use crate::Counter;
+
+pub struct CounterFilter{
+    include: Option<Vec<String>>,
+    exclude: Vec<String>,
+    include_regex: Vec<Regex>,
+    exclude_regex: Vec<Regex>,
+    rename: HashMap<String, String>,
+    scale: HashMap<String, f64>,
+}
+
+impl CounterFilter{
+    pub fn new(counter: &Counter) -> anyhow::Result<CounterFilter>{
+        let compile = |patterns: &Option<Vec<String>>| -> anyhow::Result<Vec<Regex>>{
+            let mut regex_list = Vec::new();
+            for pattern in patterns.iter().flatten(){
+                let re = Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid regex {}: {}", pattern, e))?;
+                regex_list.push(re);
+            }
+            Ok(regex_list)
+        };
+        Ok(CounterFilter{
+            include: counter.include.clone(),
+            exclude: counter.exclude.clone().unwrap_or_default(),
+            include_regex: compile(&counter.include_regex)?,
+            exclude_regex: compile(&counter.exclude_regex)?,
+            rename: counter.rename.clone().unwrap_or_default(),
+            scale: counter.scale.clone().unwrap_or_default(),
+        })
+    }
+
+    // a file is exported if it matches any include rule (or no include
+    // rules are configured) and matches no exclude rule
+    pub fn matches(&self, key: &str) -> bool{
+        let has_include = self.include.is_some() || !self.include_regex.is_empty();
+        let included = !has_include
+            || self.include.iter().flatten().any(|k| k == key)
+            || self.include_regex.iter().any(|re| re.is_match(key));
+        let excluded = self.exclude.iter().any(|k| k == key)
+            || self.exclude_regex.iter().any(|re| re.is_match(key));
+        included && !excluded
+    }
+
+    // the metric name of a counter file, e.g. a file named rx-bytes is
+    // exported as rx_bytes
+    pub fn name(&self, key: &str) -> String{
+        metric_name(self.rename.get(key).map(|name| name.as_str()).unwrap_or(key))
+    }
+
+    pub fn scale(&self, key: &str, value: u64) -> u64{
+        match self.scale.get(key){
+            Some(factor) => (value as f64 * factor).round() as u64,
+            None => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn filter(config: &str) -> anyhow::Result<CounterFilter>{
+        let counter: Counter = serde_yaml::from_str(config).unwrap();
+        CounterFilter::new(&counter)
+    }
+
+    #[test]
+    fn test_all_files_without_include(){
+        let filter = filter("paths: [/sys/class/infiniband/mlx5_0/ports/1/counters]").unwrap();
+        assert!(filter.matches("port_rcv_data"));
+        assert!(filter.matches("symbol_error"));
+    }
+
+    #[test]
+    fn test_include_and_exclude(){
+        let filter = filter(r#"
+paths: [/sys/class/infiniband/mlx5_0/ports/1/counters]
+include: [port_rcv_data, port_xmit_data]
+include_regex: ["^port_.*_packets$"]
+exclude: [port_xmit_data]
+exclude_regex: ["^port_multicast_"]
+"#).unwrap();
+        assert!(filter.matches("port_rcv_data"));
+        assert!(filter.matches("port_rcv_packets"));
+        // exclude rules win over include rules
+        assert!(!filter.matches("port_xmit_data"));
+        assert!(!filter.matches("port_multicast_rcv_packets"));
+        assert!(!filter.matches("symbol_error"));
+    }
+
+    #[test]
+    fn test_empty_include_matches_nothing(){
+        let filter = filter("paths: [/counters]\ninclude: []").unwrap();
+        assert!(!filter.matches("port_rcv_data"));
+    }
+
+    #[test]
+    fn test_invalid_regex(){
+        assert!(filter("paths: [/counters]\ninclude_regex: [\"(\"]").is_err());
+    }
+
+    #[test]
+    fn test_rename_and_scale(){
+        let filter = filter(r#"
+paths: [/counters]
+rename: {port_rcv_data: port_rcv_bytes}
+scale: {port_rcv_data: 4, link_downed: 0.5}
+"#).unwrap();
+        assert_eq!(filter.name("port_rcv_data"), "port_rcv_bytes");
+        assert_eq!(filter.name("symbol_error"), "symbol_error");
+        assert_eq!(filter.name("np-cnp-sent"), "np_cnp_sent");
+        assert_eq!(filter.scale("port_rcv_data", 100), 400);
+        assert_eq!(filter.scale("link_downed", 3), 2);
+        assert_eq!(filter.scale("symbol_error", 7), 7);
+    }
+}
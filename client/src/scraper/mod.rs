@@ -1 +1,2 @@
-pub mod scraper;
\ No newline at end of file
+pub mod scraper;
+pub mod filter;
\ No newline at end of file
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use log::info;
-use crate::{collector::collector::CollectorMetrics, grpc_client::grpc_client::Client, Counter};
+use collector_protocol::{collector::collector::CollectorMetrics, sender::sender::Client};
+use tokio::sync::oneshot;
+use crate::{counter_path, Counter};
+use super::filter::CounterFilter;
 
 pub struct Scraper{
     global_labels: HashMap<String, String>,
@@ -8,26 +11,48 @@ pub struct Scraper{
     counters: Vec<Counter>,
     client: Client,
     interval: u64,
+    root: Option<String>,
+    rx: tokio::sync::mpsc::Receiver<(Vec<Counter>, oneshot::Sender<()>)>,
+    scraper_client: ScraperClient,
 }
 
 impl Scraper{
-    pub fn new(global_labels: HashMap<String,String>, counters: Vec<Counter>, client: Client, interval: u64, namespace: Option<String>) -> Scraper{
+    pub fn new(global_labels: HashMap<String,String>, counters: Vec<Counter>, client: Client, interval: u64, namespace: Option<String>, root: Option<String>) -> Scraper{
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
         Scraper{
             global_labels,
             namespace,
             counters,
             client,
             interval,
+            root,
+            rx,
+            scraper_client: ScraperClient::new(tx),
         }
     }
-    pub async fn scrape(&self) -> anyhow::Result<()>{
+
+    pub fn client(&self) -> ScraperClient{
+        self.scraper_client.clone()
+    }
+
+    pub async fn scrape(mut self) -> anyhow::Result<()>{
         let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(self.interval));
         info!("Starting scraper at interval: {} ms", self.interval);
         let mut rate_map = HashMap::new();
+        let mut filters = self.counters.iter().map(CounterFilter::new).collect::<anyhow::Result<Vec<_>>>()?;
         loop{
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {},
+                Some((counters, updated)) = self.rx.recv() => {
+                    info!("Updating counters: {:?}", counters);
+                    filters = counters.iter().map(CounterFilter::new).collect::<anyhow::Result<Vec<_>>>()?;
+                    self.counters = counters;
+                    let _ = updated.send(());
+                    continue;
+                }
+            }
             info!("Scraping counters: {:?}", self.counters);
-            for counter in &self.counters{
+            for (counter, filter) in self.counters.iter().zip(&filters){
                 let mut metrics = HashMap::new();
                 let mut labels = if let Some(counter_labels) = &counter.labels{
                     counter_labels.clone()
@@ -36,7 +61,7 @@ impl Scraper{
                 };
                 labels.extend(self.global_labels.clone());
                 for path in &counter.paths{
-                    let files = match std::fs::read_dir(path){
+                    let files = match std::fs::read_dir(counter_path(self.root.as_deref(), path)){
                         Ok(files) => files,
                         Err(_e) => {
                             continue;
@@ -54,19 +79,24 @@ impl Scraper{
                             continue;
                         }
                         let key = file.file_name().into_string().map_err(|_| anyhow::anyhow!("Invalid file name"))?;
-                        let value = read_counter(path.to_str().ok_or(anyhow::anyhow!("Invalid path"))?);
-                        metrics.insert(key.to_string(), value);
+                        if !filter.matches(&key){
+                            continue;
+                        }
+                        let name = filter.name(&key);
+                        let value = filter.scale(&key, read_counter(path.to_str().ok_or(anyhow::anyhow!("Invalid path"))?));
+                        metrics.insert(name.clone(), value);
                         if let Some(rate_keys) = &counter.rate_keys{
                             for rate_key in rate_keys{
                                 if rate_key == &key{
-                                    let prev_rate = rate_map.get(&key).unwrap_or(&0);
+                                    // keyed by path as ports share the file names
+                                    let prev_rate = rate_map.get(&path).unwrap_or(&0);
                                     let rate = if value >= *prev_rate{
                                         value - *prev_rate
                                     } else {
                                         value
                                     };
-                                    metrics.insert(format!("{}_rate", key), rate);
-                                    rate_map.insert(key.clone(), value);
+                                    metrics.insert(format!("{}_rate", name), rate);
+                                    rate_map.insert(path.clone(), value);
                                 }
                             }
 
@@ -87,6 +117,28 @@ impl Scraper{
     }
 }
 
+#[derive(Clone)]
+pub struct ScraperClient{
+    tx: tokio::sync::mpsc::Sender<(Vec<Counter>, oneshot::Sender<()>)>,
+}
+
+impl ScraperClient{
+    fn new(tx: tokio::sync::mpsc::Sender<(Vec<Counter>, oneshot::Sender<()>)>) -> ScraperClient{
+        ScraperClient{
+            tx,
+        }
+    }
+
+    // returns once the scraper scrapes the new counters, the metrics of
+    // the previous ones are no longer sent
+    pub async fn update_counters(&self, counters: Vec<Counter>) -> anyhow::Result<()>{
+        let (updated_tx, updated_rx) = oneshot::channel();
+        self.tx.send((counters, updated_tx)).await?;
+        updated_rx.await?;
+        Ok(())
+    }
+}
+
 fn read_counter(path: &str) -> u64
 {
     let v = match std::fs::read_to_string(path){
@@ -95,10 +147,5 @@ fn read_counter(path: &str) -> u64
             return 0;
         }
     };
-    match v.trim().parse::<u64>(){
-        Ok(v) => v,
-        Err(_e) => {
-            0
-        }
-    }
+    v.trim().parse::<u64>().unwrap_or_default()
 }
\ No newline at end of file
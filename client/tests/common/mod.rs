@@ -0,0 +1,182 @@
+// Fake /sys/class/infiniband tree of mlx5 devices for the scraper and
+// reloader tests, and helpers to run them against a collector-server.
+// Every port gets the usual counters and hw_counters files, the counters
+// start at a base value and grow by a fixed amount per second times the port
+// number so ports can be told apart by their rates.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use collector_client::Counter;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+// file below the port directory, base value, increment per second
+pub const PORT_COUNTERS: [(&str, u64, u64); 8] = [
+    ("counters/port_rcv_data", 1_000_000, 250_000),
+    ("counters/port_xmit_data", 2_000_000, 500_000),
+    ("counters/port_rcv_packets", 10_000, 1_000),
+    ("counters/port_xmit_packets", 20_000, 2_000),
+    ("counters/symbol_error", 0, 0),
+    ("counters/link_downed", 1, 0),
+    ("hw_counters/out_of_buffer", 0, 10),
+    ("hw_counters/np_cnp_sent", 0, 5),
+];
+
+static TREES: AtomicUsize = AtomicUsize::new(0);
+
+pub struct FakeSysfs{
+    root: PathBuf,
+    ports: Vec<(String, u32)>,
+    start: Instant,
+}
+
+impl FakeSysfs{
+    // creates the tree for the (device, number of ports) list below a new
+    // temporary directory
+    pub fn new(devices: &[(&str, u32)]) -> std::io::Result<FakeSysfs>{
+        let root = std::env::temp_dir().join(format!("collector-client-sysfs-{}-{}", std::process::id(), TREES.fetch_add(1, Ordering::SeqCst)));
+        let mut ports = Vec::new();
+        for (device, port_count) in devices{
+            for port in 1..=*port_count{
+                ports.push((device.to_string(), port));
+            }
+        }
+        let sysfs = FakeSysfs{
+            root,
+            ports,
+            start: Instant::now(),
+        };
+        for (device, port) in &sysfs.ports{
+            let port_dir = sysfs.root.join(port_path(device, *port).trim_start_matches('/'));
+            std::fs::create_dir_all(port_dir.join("counters"))?;
+            std::fs::create_dir_all(port_dir.join("hw_counters"))?;
+        }
+        sysfs.update()?;
+        Ok(sysfs)
+    }
+
+    pub fn root(&self) -> String{
+        self.root.to_string_lossy().to_string()
+    }
+
+    // value of a counter of a port after the given time
+    pub fn value(file: &str, port: u32, elapsed: Duration) -> u64{
+        let (_, base, per_sec) = PORT_COUNTERS.iter().find(|(f, _, _)| f.ends_with(file)).expect("unknown counter");
+        base + (per_sec * port as u64) * elapsed.as_millis() as u64 / 1000
+    }
+
+    // writes the counter values for the time passed since the tree was
+    // created
+    pub fn update(&self) -> std::io::Result<()>{
+        self.update_at(self.start.elapsed())
+    }
+
+    // writes the counter values after the given time, every file is
+    // replaced at once so the scraper never reads a partial value. Ports
+    // whose directory was removed stay gone.
+    pub fn update_at(&self, elapsed: Duration) -> std::io::Result<()>{
+        let tmp = self.root.join("tmp");
+        for (device, port) in &self.ports{
+            let port_dir = self.root.join(port_path(device, *port).trim_start_matches('/'));
+            for (file, _, _) in PORT_COUNTERS{
+                std::fs::write(&tmp, format!("{}\n", Self::value(file, *port, elapsed)))?;
+                if let Err(e) = std::fs::rename(&tmp, port_dir.join(file)){
+                    if port_dir.exists(){
+                        return Err(e);
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // removes the directory of a port while the counters may be updated
+    #[allow(dead_code)]
+    pub fn remove_port(root: &str, device: &str, port: u32) -> std::io::Result<()>{
+        let port_dir = format!("{}{}", root, port_path(device, port));
+        let removed = format!("{}-removed", port_dir);
+        std::fs::rename(&port_dir, &removed)?;
+        std::fs::remove_dir_all(&removed)
+    }
+
+    // keeps updating the counters until the handle is aborted, which also
+    // removes the tree, only used by the reloader tests
+    #[allow(dead_code)]
+    pub fn run(self, every: Duration) -> JoinHandle<()>{
+        tokio::spawn(async move{
+            loop{
+                tokio::time::sleep(every).await;
+                if let Err(e) = self.update(){
+                    panic!("Failed to update {:?}: {}", self.root, e);
+                }
+            }
+        })
+    }
+}
+
+impl Drop for FakeSysfs{
+    fn drop(&mut self){
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+// the port directory as configured in the counter paths
+pub fn port_path(device: &str, port: u32) -> String{
+    format!("/sys/class/infiniband/{}/ports/{}", device, port)
+}
+
+pub fn free_address() -> String{
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+pub async fn wait_for(address: &str){
+    for _ in 0..50{
+        if TcpStream::connect(address).await.is_ok(){
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("{} is not listening", address);
+}
+
+pub async fn scrape(address: &str) -> anyhow::Result<String>{
+    let mut stream = TcpStream::connect(address).await?;
+    stream.write_all(format!("GET /metrics HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", address).as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+pub fn value(metrics: &str, series: &str) -> Option<u64>{
+    metrics.lines()
+        .find(|l| l.starts_with(&format!("{} ", series)))
+        .and_then(|l| l.rsplit(' ').next())
+        .and_then(|v| v.parse().ok())
+}
+
+pub fn counter(device: &str, port: u32) -> Counter{
+    let port_dir = port_path(device, port);
+    Counter{
+        paths: vec![format!("{}/counters", port_dir), format!("{}/hw_counters", port_dir)],
+        labels: Some(HashMap::from([
+            ("device".to_string(), device.to_string()),
+            ("port".to_string(), port.to_string()),
+        ])),
+        rate_keys: Some(vec!["port_xmit_data".to_string(), "port_rcv_data".to_string()]),
+        include: None,
+        exclude: Some(vec!["link_downed".to_string()]),
+        include_regex: None,
+        exclude_regex: None,
+        rename: Some(HashMap::from([("port_xmit_data".to_string(), "port_xmit_bytes".to_string())])),
+        // the data counters count 4 byte words
+        scale: Some(HashMap::from([("port_xmit_data".to_string(), 4.0)])),
+    }
+}
+
+pub fn global_labels() -> HashMap<String, String>{
+    HashMap::from([("host".to_string(), "node1".to_string())])
+}
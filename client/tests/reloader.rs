@@ -0,0 +1,147 @@
+// Reloads the counters of a running scraper and checks the /metrics endpoint
+// of an in-process collector-server: the series of removed counters are
+// gone, those of added counters show up, also when the collector was
+// unreachable during the first attempt.
+use std::time::Duration;
+use collector_client::reloader::reloader::Reloader;
+use collector_client::scraper::scraper::Scraper;
+use collector_client::Config;
+use collector_protocol::sender::sender::Sender;
+use collector_server::grpc_server::grpc_server::GrpcServer;
+use collector_server::prometheus::prometheus::{Client as PrometheusClient, Prometheus};
+use tokio::task::JoinHandle;
+
+mod common;
+use common::{counter, free_address, global_labels, scrape, value, wait_for, FakeSysfs};
+
+// scrape interval in milliseconds
+const INTERVAL: u64 = 100;
+
+fn config(address: &str, root: &str, ports: &[u32]) -> Config{
+    Config{
+        address: address.to_string(),
+        namespace: Some("mlx".to_string()),
+        labels: None,
+        counters: ports.iter().map(|port| counter("mlx5_0", *port)).collect(),
+        interval: INTERVAL,
+        root: Some(root.to_string()),
+        compression: Default::default(),
+        protocol: Default::default(),
+    }
+}
+
+fn write_config(path: &str, config: &Config){
+    std::fs::write(path, serde_yaml::to_string(config).unwrap()).unwrap();
+}
+
+// starts a collector-server, aborting the returned handle stops its gRPC
+// server while the metrics stay registered
+fn start_collector(grpc_address: &str, prometheus_address: &str) -> (PrometheusClient, JoinHandle<anyhow::Result<()>>){
+    let prom_server = Prometheus::new(prometheus_address.to_string());
+    let prom_client = prom_server.client();
+    tokio::spawn(async move { prom_server.web_server().await });
+    (prom_client.clone(), start_grpc(grpc_address, prom_client))
+}
+
+fn start_grpc(grpc_address: &str, prom_client: PrometheusClient) -> JoinHandle<anyhow::Result<()>>{
+    let g_server = GrpcServer::new(grpc_address.to_string(), prom_client);
+    tokio::spawn(async move { g_server.run().await })
+}
+
+fn series(port: u32) -> String{
+    format!(r#"mlx_port_rcv_packets{{device="mlx5_0",host="node1",port="{}"}}"#, port)
+}
+
+// the metrics once the series of the ports show up and the others are gone
+async fn wait_for_ports(prometheus_address: &str, ports: &[u32]) -> String{
+    let mut metrics = String::new();
+    for _ in 0..50{
+        tokio::time::sleep(Duration::from_millis(INTERVAL)).await;
+        metrics = scrape(prometheus_address).await.unwrap_or_default();
+        if (1..=3).all(|port| value(&metrics, &series(port)).is_some() == ports.contains(&port)){
+            break;
+        }
+    }
+    metrics
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_counters(){
+    let sysfs = FakeSysfs::new(&[("mlx5_0", 3)]).unwrap();
+    let root = sysfs.root();
+    let sysfs_handle = sysfs.run(Duration::from_millis(10));
+    let config_path = format!("{}/config.yaml", root);
+    let grpc_address = free_address();
+    let prometheus_address = free_address();
+    start_collector(&grpc_address, &prometheus_address);
+    wait_for(&grpc_address).await;
+
+    let initial = config(&grpc_address, &root, &[1, 2]);
+    write_config(&config_path, &initial);
+    let sender = Sender::new(grpc_address.clone());
+    let scraper = Scraper::new(global_labels(), initial.counters.clone(), sender.client(), INTERVAL, initial.namespace.clone(), Some(root.clone()));
+    let mut reloader = Reloader::new(config_path.clone(), initial, global_labels(), sender.client(), scraper.client());
+    reloader.register().await.unwrap();
+    let scraper_handle = tokio::spawn(scraper.scrape());
+    let client_handle = tokio::spawn(sender.run());
+    let metrics = wait_for_ports(&prometheus_address, &[1, 2]).await;
+    assert!(value(&metrics, &series(2)).is_some(), "{} not found in\n{}", series(2), metrics);
+
+    // port 1 is gone and removed from the config, port 3 added. The series
+    // of port 1 are unregistered without its files and the scraper must not
+    // bring them back.
+    FakeSysfs::remove_port(&root, "mlx5_0", 1).unwrap();
+    write_config(&config_path, &config(&grpc_address, &root, &[2, 3]));
+    reloader.reload().await.unwrap();
+    let metrics = wait_for_ports(&prometheus_address, &[2, 3]).await;
+    tokio::time::sleep(Duration::from_millis(3 * INTERVAL)).await;
+    let metrics_later = scrape(&prometheus_address).await.unwrap();
+    for metrics in [&metrics, &metrics_later]{
+        assert!(value(metrics, &series(1)).is_none(), "{} found in\n{}", series(1), metrics);
+        assert!(value(metrics, &series(3)).is_some(), "{} not found in\n{}", series(3), metrics);
+    }
+    scraper_handle.abort();
+    client_handle.abort();
+    sysfs_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_retried_after_failure(){
+    let sysfs = FakeSysfs::new(&[("mlx5_0", 3)]).unwrap();
+    let root = sysfs.root();
+    let sysfs_handle = sysfs.run(Duration::from_millis(10));
+    let config_path = format!("{}/config.yaml", root);
+    let grpc_address = free_address();
+    let prometheus_address = free_address();
+    let (prom_client, grpc_handle) = start_collector(&grpc_address, &prometheus_address);
+    wait_for(&grpc_address).await;
+
+    let initial = config(&grpc_address, &root, &[1]);
+    write_config(&config_path, &initial);
+    let sender = Sender::new(grpc_address.clone());
+    let scraper = Scraper::new(global_labels(), initial.counters.clone(), sender.client(), INTERVAL, initial.namespace.clone(), Some(root.clone()));
+    let mut reloader = Reloader::new(config_path.clone(), initial, global_labels(), sender.client(), scraper.client());
+    reloader.register().await.unwrap();
+    let scraper_handle = tokio::spawn(scraper.scrape());
+    let client_handle = tokio::spawn(sender.run());
+
+    // the collector is unreachable during the first attempt
+    grpc_handle.abort();
+    let _ = grpc_handle.await;
+    write_config(&config_path, &config(&grpc_address, &root, &[2, 3]));
+    assert!(reloader.reload().await.is_err());
+
+    start_grpc(&grpc_address, prom_client);
+    wait_for(&grpc_address).await;
+    reloader.reload().await.unwrap();
+    let metrics = wait_for_ports(&prometheus_address, &[2, 3]).await;
+    for port in [2, 3]{
+        assert!(value(&metrics, &series(port)).is_some(), "{} not found in\n{}", series(port), metrics);
+    }
+    assert!(value(&metrics, &series(1)).is_none(), "{} found in\n{}", series(1), metrics);
+    // nothing is left to apply
+    reloader.reload().await.unwrap();
+    scraper_handle.abort();
+    client_handle.abort();
+    sysfs_handle.abort();
+}
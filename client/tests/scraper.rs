@@ -0,0 +1,107 @@
+// Scrapes a fake sysfs tree and sends the counters to an in-process
+// collector-server, whose /metrics endpoint is checked for the registered
+// metric names, labels and rates.
+use std::time::Duration;
+use collector_client::scraper::scraper::Scraper;
+use collector_client::get_metrics_metadata;
+use collector_protocol::sender::sender::Sender;
+use collector_server::grpc_server::grpc_server::GrpcServer;
+use collector_server::prometheus::prometheus::Prometheus;
+
+mod common;
+use common::{counter, free_address, global_labels, scrape, value, wait_for, FakeSysfs};
+
+// scrape interval in milliseconds
+const INTERVAL: u64 = 200;
+
+#[test]
+fn test_registered_metrics(){
+    let sysfs = FakeSysfs::new(&[("mlx5_0", 1)]).unwrap();
+    let metrics = get_metrics_metadata(counter("mlx5_0", 1), global_labels(), Some("mlx".to_string()), Some(&sysfs.root())).unwrap();
+    let mut names: Vec<&str> = metrics.metrics.keys().map(|k| k.as_str()).collect();
+    names.sort();
+    assert_eq!(names, [
+        "np_cnp_sent", "out_of_buffer", "port_rcv_data", "port_rcv_data_rate", "port_rcv_packets",
+        "port_xmit_bytes", "port_xmit_bytes_rate", "port_xmit_packets", "symbol_error",
+    ]);
+    assert_eq!(metrics.labels.get("device").map(String::as_str), Some("mlx5_0"));
+    assert_eq!(metrics.labels.get("port").map(String::as_str), Some("1"));
+    assert_eq!(metrics.labels.get("host").map(String::as_str), Some("node1"));
+
+    // the counter paths are not looked up on the host
+    let metrics = get_metrics_metadata(counter("mlx5_0", 1), global_labels(), None, Some("/nonexistent")).unwrap();
+    assert!(metrics.metrics.is_empty());
+}
+
+// the counters are only stepped by the test, so the rate of the scrape
+// seeing a step is exactly the increment of one interval
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scrape_to_metrics(){
+    let sysfs = FakeSysfs::new(&[("mlx5_0", 2)]).unwrap();
+    let root = sysfs.root();
+
+    let grpc_address = free_address();
+    let prometheus_address = free_address();
+    let prom_server = Prometheus::new(prometheus_address.clone());
+    let g_server = GrpcServer::new(grpc_address.clone(), prom_server.client());
+    tokio::spawn(async move { prom_server.web_server().await });
+    tokio::spawn(async move { g_server.run().await });
+    wait_for(&grpc_address).await;
+
+    let namespace = Some("mlx".to_string());
+    let counters = vec![counter("mlx5_0", 1), counter("mlx5_0", 2)];
+    let sender = Sender::new(grpc_address.clone());
+    for counter in &counters{
+        let metrics = get_metrics_metadata(counter.clone(), global_labels(), namespace.clone(), Some(&root)).unwrap();
+        sender.client().register_metrics(metrics).await.unwrap();
+    }
+    let scraper = Scraper::new(global_labels(), counters, sender.client(), INTERVAL, namespace, Some(root));
+    let scraper_handle = tokio::spawn(scraper.scrape());
+    let client_handle = tokio::spawn(sender.run());
+
+    let series = |name: &str, port: u32| format!(r#"mlx_{}{{device="mlx5_0",host="node1",port="{}"}}"#, name, port);
+    let xmit_bytes = |port: u32, elapsed: Duration| 4 * FakeSysfs::value("port_xmit_data", port, elapsed);
+    // the first scrapes see the initial values and a rate of 0
+    let mut metrics = String::new();
+    for _ in 0..50{
+        tokio::time::sleep(Duration::from_millis(INTERVAL)).await;
+        metrics = scrape(&prometheus_address).await.unwrap_or_default();
+        if (1..=2).all(|port| value(&metrics, &series("port_xmit_bytes_rate", port)) == Some(0)){
+            break;
+        }
+    }
+    for port in 1..=2{
+        for name in ["port_rcv_data", "port_rcv_data_rate", "port_rcv_packets", "port_xmit_packets", "symbol_error", "out_of_buffer", "np_cnp_sent"]{
+            assert!(value(&metrics, &series(name, port)).is_some(), "{} not found in\n{}", series(name, port), metrics);
+        }
+        assert_eq!(value(&metrics, &series("port_xmit_bytes", port)), Some(xmit_bytes(port, Duration::ZERO)), "port_xmit_bytes not scaled");
+        assert!(value(&metrics, &series("port_xmit_data", port)).is_none(), "port_xmit_data not renamed");
+        assert!(value(&metrics, &series("link_downed", port)).is_none(), "link_downed not excluded");
+    }
+
+    // the rate is sent with the value of the same scrape and is 0 again
+    // one interval later, /metrics is polled well within an interval
+    let step = Duration::from_millis(INTERVAL);
+    sysfs.update_at(step).unwrap();
+    let mut rates = [None, None];
+    for _ in 0..100{
+        tokio::time::sleep(Duration::from_millis(INTERVAL / 20)).await;
+        metrics = scrape(&prometheus_address).await.unwrap_or_default();
+        for port in 1..=2{
+            if rates[port as usize - 1].is_none() && value(&metrics, &series("port_xmit_bytes", port)) == Some(xmit_bytes(port, step)){
+                rates[port as usize - 1] = value(&metrics, &series("port_xmit_bytes_rate", port));
+            }
+        }
+        if rates.iter().all(Option::is_some){
+            break;
+        }
+    }
+    scraper_handle.abort();
+    client_handle.abort();
+
+    // increments per scrape interval, port 2 counts twice as fast
+    for port in 1..=2{
+        assert_eq!(rates[port as usize - 1], Some(xmit_bytes(port, step) - xmit_bytes(port, Duration::ZERO)), "port_xmit_bytes_rate of port {}", port);
+    }
+    assert_eq!(xmit_bytes(2, step) - xmit_bytes(2, Duration::ZERO), 500_000 * 4 * 2 * INTERVAL / 1000);
+}
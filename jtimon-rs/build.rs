@@ -16,7 +16,8 @@ fn main() {
     .include_file("mod.rs")
     .compile(
         &["protos/gnmi/gnmi.proto",
-        "protos/gnmi/gnmi_ext.proto"],
+        "protos/gnmi/gnmi_ext.proto",
+        "protos/gnmi/dialout.proto"],
         &["protos/gnmi"]
     )
     .unwrap();
@@ -42,11 +43,20 @@ fn main() {
     .unwrap();
 
     tonic_build::configure()
-    .out_dir("src/collector")
+    .out_dir("src/native")
     .include_file("mod.rs")
     .compile(
-        &["../protos/collector.proto"],
-        &["../protos"]
+        &["protos/native/native.proto"],
+        &["protos/native"]
+    )
+    .unwrap();
+
+    tonic_build::configure()
+    .out_dir("src/recording")
+    .include_file("mod.rs")
+    .compile(
+        &["protos/recording/recording.proto"],
+        &["protos/recording"]
     )
     .unwrap();
 }
\ No newline at end of file
@@ -1 +0,0 @@
-pub mod collector_client;
\ No newline at end of file
@@ -0,0 +1,275 @@
+use clap::{Subcommand, ValueEnum};
+use std::collections::HashMap;
+use crate::gnmi::gnmi::{typed_value, Encoding};
+use crate::gnmi_client::gnmi_client::{format_path, notification_metrics, GnmiClient};
+use crate::grpc::grpc::Grpc;
+use crate::recorder::{recorder, replay::replay};
+use crate::telemetry::telemetry::{
+    key_value::Value, open_config_telemetry_client::OpenConfigTelemetryClient,
+    CancelSubscriptionRequest, DataEncodingRequest, GetOperationalStateRequest,
+    GetSubscriptionsRequest, ReturnCode, VerbosityLevel,
+};
+use crate::{Config, Device};
+
+// subscription id addressing all subscriptions on the device
+const ALL_SUBSCRIPTIONS: u32 = u32::MAX;
+
+#[derive(Subcommand)]
+pub enum Command{
+    /// List the telemetry subscriptions on a device
+    List{
+        /// Address of the device as configured
+        #[clap(short, long)]
+        device: String,
+        /// Only show this subscription
+        #[clap(short, long)]
+        id: Option<u32>,
+    },
+    /// Cancel a telemetry subscription on a device
+    Cancel{
+        #[clap(short, long)]
+        device: String,
+        #[clap(short, long)]
+        id: u32,
+    },
+    /// Show the operational state of the telemetry agent on a device
+    Opstate{
+        #[clap(short, long)]
+        device: String,
+        #[clap(short, long)]
+        id: Option<u32>,
+        #[clap(short, long, value_enum, default_value = "brief")]
+        verbosity: Verbosity,
+    },
+    /// List the data encodings supported by a device
+    Encodings{
+        #[clap(short, long)]
+        device: String,
+    },
+    /// List the models and encodings supported by the gNMI server of a device
+    Capabilities{
+        #[clap(short, long)]
+        device: String,
+    },
+    /// Get paths with gNMI, e.g. /interfaces/interface[name=et-0/0/0]/state
+    Get{
+        #[clap(short, long)]
+        device: String,
+        #[clap(short, long, required = true)]
+        path: Vec<String>,
+        #[clap(short, long, value_enum, default_value = "json")]
+        encoding: GnmiEncoding,
+        /// Print the metrics the values convert to instead of the values and
+        /// send them to the collector
+        #[clap(short, long)]
+        metrics: bool,
+    },
+    /// Send a recording through the converter to the collector using the
+    /// devices and paths of the config
+    Replay{
+        /// Recording written with the record option
+        #[clap(short, long)]
+        file: String,
+        /// Replay this many times faster than recorded, 0 for as fast as possible
+        #[clap(short, long, default_value = "1")]
+        speed: f64,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum GnmiEncoding{
+    Json,
+    JsonIetf,
+}
+
+impl From<GnmiEncoding> for Encoding{
+    fn from(encoding: GnmiEncoding) -> Self{
+        match encoding{
+            GnmiEncoding::Json => Encoding::Json,
+            GnmiEncoding::JsonIetf => Encoding::JsonIetf,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Verbosity{
+    Detail,
+    Terse,
+    Brief,
+}
+
+impl From<Verbosity> for VerbosityLevel{
+    fn from(verbosity: Verbosity) -> Self{
+        match verbosity{
+            Verbosity::Detail => VerbosityLevel::Detail,
+            Verbosity::Terse => VerbosityLevel::Terse,
+            Verbosity::Brief => VerbosityLevel::Brief,
+        }
+    }
+}
+
+pub async fn run(command: Command, config: Config) -> anyhow::Result<()>{
+    match command{
+        Command::List{ device, id } => {
+            let mut client = telemetry_client(&config, &device).await?;
+            let req = GetSubscriptionsRequest{
+                subscription_id: id.unwrap_or(ALL_SUBSCRIPTIONS),
+            };
+            let reply = client.get_telemetry_subscriptions(req).await?.into_inner();
+            for subscription in reply.subscription_list{
+                let id = subscription.response.map(|r| r.subscription_id).unwrap_or_default();
+                println!("subscription {}", id);
+                for path in subscription.path_list{
+                    println!("  {} freq: {} filter: {}", path.path, path.sample_frequency, path.filter);
+                }
+            }
+        },
+        Command::Cancel{ device, id } => {
+            let mut client = telemetry_client(&config, &device).await?;
+            let req = CancelSubscriptionRequest{
+                subscription_id: id,
+            };
+            let reply = client.cancel_telemetry_subscription(req).await?.into_inner();
+            if reply.code() != ReturnCode::Success{
+                return Err(anyhow::anyhow!("Failed to cancel subscription {}: {} {}", id, reply.code().as_str_name(), reply.code_str));
+            }
+            println!("cancelled subscription {}", id);
+        },
+        Command::Opstate{ device, id, verbosity } => {
+            let mut client = telemetry_client(&config, &device).await?;
+            let mut req = GetOperationalStateRequest{
+                subscription_id: id.unwrap_or(ALL_SUBSCRIPTIONS),
+                ..Default::default()
+            };
+            req.set_verbosity(verbosity.into());
+            let reply = client.get_telemetry_operational_state(req).await?.into_inner();
+            for kv in reply.kv{
+                let value = kv.value.as_ref().map(format_value).unwrap_or_default();
+                println!("{}: {}", kv.key, value);
+            }
+        },
+        Command::Encodings{ device } => {
+            let mut client = telemetry_client(&config, &device).await?;
+            let reply = client.get_data_encodings(DataEncodingRequest{}).await?.into_inner();
+            for encoding in reply.encoding_list(){
+                println!("{}", encoding.as_str_name());
+            }
+        },
+        Command::Capabilities{ device } => {
+            let mut client = gnmi_client(&config, &device).await?;
+            let reply = client.capabilities().await?;
+            println!("gNMI version: {}", reply.g_nmi_version);
+            println!("encodings:");
+            for encoding in reply.supported_encodings(){
+                println!("  {}", encoding.as_str_name());
+            }
+            println!("models:");
+            for model in reply.supported_models{
+                println!("  {} {} {}", model.name, model.organization, model.version);
+            }
+        },
+        Command::Get{ device, path, encoding, metrics } => {
+            let namespace = find_device(&config, &device)?.namespace.clone();
+            let mut client = gnmi_client(&config, &device).await?;
+            let notifications = client.get(&path, encoding.into()).await?;
+            let labels = HashMap::from([
+                ("namespace".to_string(), namespace),
+                ("system_id".to_string(), device.clone()),
+            ]);
+            if metrics{
+                let col_client = config.collector.sender();
+                let col_client_client = col_client.client();
+                let handle = tokio::spawn(col_client.run());
+                let mut sent = 0;
+                for notification in notifications{
+                    for collector_metrics in notification_metrics(&notification, &labels){
+                        let mut labels: Vec<String> = collector_metrics.labels.iter().map(|(k, v)| format!("{}={:?}", k, v)).collect();
+                        labels.sort();
+                        let mut values: Vec<(&String, &u64)> = collector_metrics.metrics.iter().collect();
+                        values.sort();
+                        for (name, value) in values{
+                            println!("{}{{{}}} {}", name, labels.join(","), value);
+                        }
+                        col_client_client.send(collector_metrics).await?;
+                        sent += 1;
+                    }
+                }
+                drop(col_client_client);
+                handle.await??;
+                println!("sent {} metrics", sent);
+                return Ok(());
+            }
+            for notification in notifications{
+                for update in notification.update{
+                    let mut path = notification.prefix.clone().unwrap_or_default();
+                    path.elem.extend(update.path.map(|p| p.elem).unwrap_or_default());
+                    let value = update.val.and_then(|v| v.value).map(|v| format_typed_value(&v)).unwrap_or_default();
+                    println!("{}: {}", format_path(&path), value);
+                }
+            }
+        },
+        Command::Replay{ file, speed } => {
+            let records = recorder::read(&file)?;
+            println!("replaying {}", file);
+            let col_client = config.collector.sender();
+            let col_client_client = col_client.client();
+            let handle = tokio::spawn(col_client.run());
+            let sent = replay(&config, records, speed, &col_client_client).await?;
+            drop(col_client_client);
+            handle.await??;
+            println!("sent {} metrics", sent);
+        },
+    }
+    Ok(())
+}
+
+async fn telemetry_client(config: &Config, address: &str) -> anyhow::Result<OpenConfigTelemetryClient<tonic::transport::Channel>>{
+    let device = find_device(config, address)?;
+    let (user, password) = device.credentials(config.credentials_file.as_deref())?;
+    let channel = Grpc::login(&device.address, device.tls.as_ref(), user, password, device.client_id()).await?;
+    Ok(OpenConfigTelemetryClient::new(channel))
+}
+
+async fn gnmi_client(config: &Config, address: &str) -> anyhow::Result<GnmiClient>{
+    let device = find_device(config, address)?;
+    let (user, password) = device.credentials(config.credentials_file.as_deref())?;
+    GnmiClient::connect(&device.address, device.tls.as_ref(), user, password).await
+}
+
+fn find_device<'a>(config: &'a Config, address: &str) -> anyhow::Result<&'a Device>{
+    config.devices.iter()
+        .find(|d| d.address == address)
+        .ok_or(anyhow::anyhow!("Device {} not found in config", address))
+}
+
+pub fn format_value(value: &Value) -> String{
+    match value{
+        Value::DoubleValue(v) => v.to_string(),
+        Value::IntValue(v) => v.to_string(),
+        Value::UintValue(v) => v.to_string(),
+        Value::SintValue(v) => v.to_string(),
+        Value::BoolValue(v) => v.to_string(),
+        Value::StrValue(v) => v.clone(),
+        Value::BytesValue(v) => format!("{:?}", v),
+        Value::FloatValue(v) => v.to_string(),
+        Value::LeaflistValue(v) => format!("{:?}", v),
+    }
+}
+
+fn format_typed_value(value: &typed_value::Value) -> String{
+    match value{
+        typed_value::Value::JsonVal(v) | typed_value::Value::JsonIetfVal(v) => {
+            match serde_json::from_slice::<serde_json::Value>(v){
+                Ok(json) => serde_json::to_string_pretty(&json).unwrap_or_default(),
+                Err(_) => String::from_utf8_lossy(v).to_string(),
+            }
+        },
+        typed_value::Value::StringVal(v) | typed_value::Value::AsciiVal(v) => v.clone(),
+        typed_value::Value::IntVal(v) => v.to_string(),
+        typed_value::Value::UintVal(v) => v.to_string(),
+        typed_value::Value::BoolVal(v) => v.to_string(),
+        typed_value::Value::FloatVal(v) => v.to_string(),
+        typed_value::Value::DoubleVal(v) => v.to_string(),
+        _ => format!("{:?}", value),
+    }
+}
@@ -0,0 +1,284 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use log::{info, warn};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity, Uri};
+use crate::Tls;
+
+// The connection mode is derived from the tls section of a device:
+// - no tls section or no fields set: plaintext
+// - insecure_skip_verify: TLS without verifying the device certificate,
+//   a client certificate is presented if cert_file and key_file are set
+// - ca_file: TLS verifying the device certificate, against server_name if
+//   set, server_name without ca_file or insecure_skip_verify is an error
+// - ca_file, cert_file and key_file: mutual TLS
+pub async fn connect(address: &str, tls: Option<&Tls>) -> anyhow::Result<Channel>{
+    let tls = match tls{
+        Some(tls) if !tls.is_empty() => tls,
+        _ => {
+            info!("Connecting to http://{}", address);
+            let channel = Endpoint::from_shared(format!("http://{}", address))?
+                .connect()
+                .await?;
+            return Ok(channel);
+        }
+    };
+    let identity = match (&tls.cert_file, &tls.key_file){
+        (Some(cert_file), Some(key_file)) => Some((read_file(cert_file)?, read_file(key_file)?)),
+        (None, None) => None,
+        (Some(_), None) => return Err(anyhow::anyhow!("cert_file is set but key_file is missing for {}", address)),
+        (None, Some(_)) => return Err(anyhow::anyhow!("key_file is set but cert_file is missing for {}", address)),
+    };
+    if tls.insecure_skip_verify{
+        warn!("Connecting to {} without verifying the device certificate", address);
+        return connect_insecure(address, tls, identity).await;
+    }
+    let ca_file = tls.ca_file.as_ref().ok_or(anyhow::anyhow!("ca_file is required to verify {}, set insecure_skip_verify to skip verification", address))?;
+    let mut tls_config = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(read_file(ca_file)?));
+    if let Some(server_name) = &tls.server_name{
+        tls_config = tls_config.domain_name(server_name.clone());
+    }
+    if let Some((crt, key)) = identity{
+        tls_config = tls_config.identity(Identity::from_pem(crt, key));
+    }
+    let ep_address = format!("https://{}", address);
+    info!("Connecting to {}", ep_address);
+    let channel = Channel::from_shared(ep_address)?
+        .tls_config(tls_config)?
+        .connect()
+        .await?;
+    Ok(channel)
+}
+
+async fn connect_insecure(address: &str, tls: &Tls, identity: Option<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<Channel>{
+    let builder = rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoVerifier));
+    let mut config = match identity{
+        Some((crt, key)) => {
+            let certs = rustls_pemfile::certs(&mut crt.as_slice()).collect::<Result<Vec<_>, _>>()?;
+            let key: PrivateKeyDer = rustls_pemfile::private_key(&mut key.as_slice())?
+                .ok_or(anyhow::anyhow!("No private key found in key_file for {}", address))?;
+            builder.with_client_auth_cert(certs, key)?
+        },
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    let config = Arc::new(config);
+    let server_name = match &tls.server_name{
+        Some(server_name) => ServerName::try_from(server_name.clone())?,
+        None => address_server_name(address)?,
+    };
+    // the endpoint uses http as the TLS handshake is done by the connector
+    let connector = tower::service_fn(move |uri: Uri| {
+        let config = config.clone();
+        let server_name = server_name.clone();
+        async move {
+            let host = uri_host(&uri).to_string();
+            let port = uri.port_u16().unwrap_or(443);
+            let tcp = TcpStream::connect((host, port)).await?;
+            TlsConnector::from(config).connect(server_name, tcp).await
+        }
+    });
+    info!("Connecting to https://{}", address);
+    let channel = Endpoint::from_shared(format!("http://{}", address))?
+        .connect_with_connector(connector)
+        .await?;
+    Ok(channel)
+}
+
+// the host of the address as server name, IP literals such as [::1]:32767
+// are IP addresses
+fn address_server_name(address: &str) -> anyhow::Result<ServerName<'static>>{
+    let uri: Uri = format!("http://{}", address).parse()?;
+    let host = uri_host(&uri);
+    match host.parse::<IpAddr>(){
+        Ok(ip) => Ok(ServerName::IpAddress(ip.into())),
+        Err(_) => Ok(ServerName::try_from(host.to_string())?),
+    }
+}
+
+// the host of a URI without the brackets of an IPv6 address
+fn uri_host(uri: &Uri) -> &str{
+    uri.host().unwrap_or_default().trim_start_matches('[').trim_end_matches(']')
+}
+
+pub fn read_file(path: &str) -> anyhow::Result<Vec<u8>>{
+    std::fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path, e))
+}
+
+#[derive(Debug)]
+struct NoVerifier;
+
+impl ServerCertVerifier for NoVerifier{
+    fn verify_server_cert(&self, _end_entity: &CertificateDer<'_>, _intermediates: &[CertificateDer<'_>], _server_name: &ServerName<'_>, _ocsp_response: &[u8], _now: UnixTime) -> Result<ServerCertVerified, rustls::Error>{
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error>{
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error>{
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme>{
+        rustls::crypto::ring::default_provider().signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::path::PathBuf;
+    use mock_junos::mock::mock::MockServer;
+    use mock_junos::script::script::Script;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use crate::telemetry::telemetry::{open_config_telemetry_client::OpenConfigTelemetryClient, DataEncodingRequest};
+    use super::*;
+
+    // a CA with a device and a client certificate and a second CA, written
+    // to a temporary directory
+    struct Pki{
+        dir: PathBuf,
+    }
+
+    impl Pki{
+        fn new(name: &str) -> Pki{
+            let dir = std::env::temp_dir().join(format!("jtimon-pki-{}-{}", std::process::id(), name));
+            std::fs::create_dir_all(&dir).unwrap();
+            let pki = Pki{ dir };
+            let (ca, ca_key) = pki.ca("ca");
+            pki.ca("other_ca");
+            for (name, host) in [("device", "localhost"), ("client", "jtimon")]{
+                let key = KeyPair::generate().unwrap();
+                let cert = CertificateParams::new(vec![host.to_string()]).unwrap().signed_by(&key, &ca, &ca_key).unwrap();
+                std::fs::write(pki.dir.join(format!("{}.crt", name)), cert.pem()).unwrap();
+                std::fs::write(pki.dir.join(format!("{}.key", name)), key.serialize_pem()).unwrap();
+            }
+            pki
+        }
+
+        fn ca(&self, name: &str) -> (rcgen::Certificate, KeyPair){
+            let key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let cert = params.self_signed(&key).unwrap();
+            std::fs::write(self.dir.join(format!("{}.crt", name)), cert.pem()).unwrap();
+            (cert, key)
+        }
+
+        fn path(&self, file: &str) -> Option<String>{
+            Some(self.dir.join(file).to_string_lossy().to_string())
+        }
+
+        fn read(&self, file: &str) -> Vec<u8>{
+            std::fs::read(self.dir.join(file)).unwrap()
+        }
+
+        async fn device(&self, mutual: bool) -> String{
+            let client_ca = mutual.then(|| self.read("ca.crt"));
+            MockServer::new(Script::default())
+                .with_tls(&self.read("device.crt"), &self.read("device.key"), client_ca.as_deref())
+                .spawn().await.unwrap().to_string()
+        }
+    }
+
+    impl Drop for Pki{
+        fn drop(&mut self){
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn tls() -> Tls{
+        Tls{
+            cert_file: None,
+            key_file: None,
+            ca_file: None,
+            server_name: None,
+            insecure_skip_verify: false,
+        }
+    }
+
+    // connects and makes a call, as the TLS handshake may only fail on the
+    // first request
+    async fn encodings(address: &str, tls: Option<&Tls>) -> anyhow::Result<()>{
+        let channel = connect(address, tls).await?;
+        OpenConfigTelemetryClient::new(channel).get_data_encodings(DataEncodingRequest{}).await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_server_name(){
+        assert_eq!(address_server_name("[::1]:32767").unwrap(), ServerName::IpAddress("::1".parse::<IpAddr>().unwrap().into()));
+        assert_eq!(address_server_name("10.0.0.1:50051").unwrap(), ServerName::IpAddress("10.0.0.1".parse::<IpAddr>().unwrap().into()));
+        assert_eq!(address_server_name("qfx1:32767").unwrap(), ServerName::try_from("qfx1").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_connect_plaintext(){
+        let address = MockServer::new(Script::default()).spawn().await.unwrap().to_string();
+        encodings(&address, None).await.unwrap();
+        encodings(&address, Some(&tls())).await.unwrap();
+        // a server name alone is no reason to fall back to plaintext
+        let server_name = Tls{ server_name: Some("localhost".to_string()), ..tls() };
+        assert!(!server_name.is_empty());
+        assert!(encodings(&address, Some(&server_name)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_verified(){
+        let pki = Pki::new("verified");
+        let address = pki.device(false).await;
+        let verified = Tls{ ca_file: pki.path("ca.crt"), server_name: Some("localhost".to_string()), ..tls() };
+        encodings(&address, Some(&verified)).await.unwrap();
+        // the certificate is issued for localhost, not the address
+        let no_server_name = Tls{ server_name: None, ..verified.clone() };
+        assert!(encodings(&address, Some(&no_server_name)).await.is_err());
+        let other_ca = Tls{ ca_file: pki.path("other_ca.crt"), ..verified.clone() };
+        assert!(encodings(&address, Some(&other_ca)).await.is_err());
+        let missing_key = Tls{ cert_file: pki.path("client.crt"), ..verified };
+        assert!(encodings(&address, Some(&missing_key)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_mutual(){
+        let pki = Pki::new("mutual");
+        let address = pki.device(true).await;
+        let verified = Tls{ ca_file: pki.path("ca.crt"), server_name: Some("localhost".to_string()), ..tls() };
+        assert!(encodings(&address, Some(&verified)).await.is_err());
+        let mutual = Tls{ cert_file: pki.path("client.crt"), key_file: pki.path("client.key"), ..verified };
+        encodings(&address, Some(&mutual)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connect_insecure(){
+        let pki = Pki::new("insecure");
+        let address = pki.device(false).await;
+        let insecure = Tls{ insecure_skip_verify: true, ..tls() };
+        encodings(&address, Some(&insecure)).await.unwrap();
+        // the client certificate is presented without verifying the device
+        let address = pki.device(true).await;
+        assert!(encodings(&address, Some(&insecure)).await.is_err());
+        let mutual = Tls{ cert_file: pki.path("client.crt"), key_file: pki.path("client.key"), ..insecure };
+        encodings(&address, Some(&mutual)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connect_insecure_ipv6(){
+        let pki = Pki::new("insecure_ipv6");
+        let address = MockServer::new(Script::default())
+            .with_tls(&pki.read("device.crt"), &pki.read("device.key"), None)
+            .spawn_on("[::1]:0").await.unwrap().to_string();
+        let insecure = Tls{ insecure_skip_verify: true, ..tls() };
+        encodings(&address, Some(&insecure)).await.unwrap();
+    }
+}
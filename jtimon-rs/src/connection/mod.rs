@@ -0,0 +1 @@
+pub mod connection;
\ No newline at end of file
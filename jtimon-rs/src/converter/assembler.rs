@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use log::warn;
+use crate::telemetry::telemetry::OpenConfigData;
+use crate::Path as ConfigPath;
+
+// Sensors of paths subscribed with need_eom can spread one sample across
+// several messages, the last one carrying an EOM. The assembler collects the
+// messages of a sample per (system_id, component, sensor path) and hands out
+// the complete sample on EOM. Messages of other paths are complete samples.
+// Sequence numbers are checked per sensor, after a gap the messages up to
+// the next EOM are dropped instead of being converted with half updated
+// counters.
+pub struct Assembler{
+    need_eom: Vec<String>,
+    sensors: HashMap<SensorKey, Sensor>,
+}
+
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+struct SensorKey{
+    system_id: String,
+    component_id: u32,
+    path: String,
+}
+
+#[derive(Default)]
+struct Sensor{
+    last_sequence_number: Option<u64>,
+    pending: Option<OpenConfigData>,
+    // a gap was seen, messages are dropped until the next EOM
+    skipping: bool,
+}
+
+impl Assembler{
+    pub fn new(paths: &[ConfigPath]) -> Assembler{
+        Assembler{
+            need_eom: paths.iter().filter(|p| p.need_eom).map(|p| p.path.clone()).collect(),
+            sensors: HashMap::new(),
+        }
+    }
+
+    // returns the sample completed by data, if any
+    pub fn add(&mut self, data: OpenConfigData) -> Option<OpenConfigData>{
+        let key = SensorKey{
+            system_id: data.system_id.clone(),
+            component_id: data.component_id,
+            path: data.path.clone(),
+        };
+        // the path is <sensor name>:<subscribed path>:<path>:<component>
+        let need_eom = data.path.split(':').nth(1).is_some_and(|p| self.need_eom.iter().any(|n| n == p));
+        let sensor = self.sensors.entry(key.clone()).or_default();
+        if let Some(last) = sensor.last_sequence_number{
+            if data.sequence_number <= last && data.sequence_number != 0{
+                warn!("Dropping duplicate or reordered message {} of {:?}, last was {}", data.sequence_number, key, last);
+                return None;
+            }
+            let expected = last.wrapping_add(1);
+            if data.sequence_number == 0 && expected != 0{
+                // a restarted sensor starts over with a new sample
+                if sensor.pending.take().is_some(){
+                    warn!("Dropping incomplete sample of restarted {:?}", key);
+                }
+                sensor.skipping = false;
+            } else if data.sequence_number != expected{
+                warn!("Sequence gap for {:?}: expected {}, got {}", key, expected, data.sequence_number);
+                if need_eom{
+                    warn!("Dropping incomplete sample of {:?}", key);
+                    sensor.pending = None;
+                    sensor.skipping = true;
+                }
+            }
+        }
+        sensor.last_sequence_number = Some(data.sequence_number);
+        if !need_eom{
+            return Some(data);
+        }
+        let eom = !data.eom.is_empty();
+        if sensor.skipping{
+            sensor.skipping = !eom;
+            return None;
+        }
+        let sample = match sensor.pending.take(){
+            Some(mut pending) => {
+                pending.sequence_number = data.sequence_number;
+                pending.kv.extend(data.kv);
+                pending.delete.extend(data.delete);
+                pending.eom = data.eom;
+                pending
+            },
+            None => data,
+        };
+        if eom{
+            Some(sample)
+        } else {
+            sensor.pending = Some(sample);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::telemetry::telemetry::{key_value::Value, Eom, KeyValue};
+
+    const PATH: &str = "sensor_1000:/interfaces/:/interfaces/:PFE";
+
+    fn paths(need_eom: bool) -> Vec<ConfigPath>{
+        serde_yaml::from_str(&format!("- path: /interfaces/\n  freq: 1000\n  need_eom: {}", need_eom)).unwrap()
+    }
+
+    fn message(sequence_number: u64, key: &str, eom: bool) -> OpenConfigData{
+        OpenConfigData{
+            system_id: "qfx1".to_string(),
+            path: PATH.to_string(),
+            sequence_number,
+            kv: vec![KeyValue{
+                key: key.to_string(),
+                value: Some(Value::UintValue(sequence_number)),
+            }],
+            eom: if eom { vec![Eom{path: "/interfaces/".to_string()}] } else { Vec::new() },
+            ..Default::default()
+        }
+    }
+
+    fn keys(data: &OpenConfigData) -> Vec<&str>{
+        data.kv.iter().map(|kv| kv.key.as_str()).collect()
+    }
+
+    #[test]
+    fn test_assemble_on_eom(){
+        let mut assembler = Assembler::new(&paths(true));
+        assert!(assembler.add(message(1, "a", false)).is_none());
+        assert!(assembler.add(message(2, "b", false)).is_none());
+        let sample = assembler.add(message(3, "c", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["a", "b", "c"]);
+        assert_eq!(sample.sequence_number, 3);
+        let sample = assembler.add(message(4, "d", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["d"]);
+    }
+
+    #[test]
+    fn test_gap_drops_partial_sample(){
+        let mut assembler = Assembler::new(&paths(true));
+        assert!(assembler.add(message(1, "a", false)).is_none());
+        // c and d may be the tail of a sample whose start was lost
+        assert!(assembler.add(message(3, "c", false)).is_none());
+        assert!(assembler.add(message(4, "d", true)).is_none());
+        let sample = assembler.add(message(5, "e", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["e"]);
+        assert!(assembler.add(message(7, "g", true)).is_none());
+        let sample = assembler.add(message(8, "h", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["h"]);
+        // a restarted sensor starts over with a complete sample
+        assert!(assembler.add(message(10, "j", false)).is_none());
+        assert!(assembler.add(message(0, "a", false)).is_none());
+        let sample = assembler.add(message(1, "b", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_sequence_number_wraps(){
+        let mut assembler = Assembler::new(&paths(true));
+        assert!(assembler.add(message(u64::MAX, "a", false)).is_none());
+        let sample = assembler.add(message(0, "b", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_duplicate_dropped(){
+        let mut assembler = Assembler::new(&paths(false));
+        assert!(assembler.add(message(1, "a", false)).is_some());
+        assert!(assembler.add(message(1, "a", false)).is_none());
+        assert!(assembler.add(message(2, "b", false)).is_some());
+        // a restarted sensor starts over at 0
+        assert!(assembler.add(message(0, "a", false)).is_some());
+    }
+
+    #[test]
+    fn test_sensors_are_independent(){
+        let mut assembler = Assembler::new(&paths(true));
+        let mut other = message(1, "x", false);
+        other.component_id = 1;
+        assert!(assembler.add(message(1, "a", false)).is_none());
+        assert!(assembler.add(other).is_none());
+        let sample = assembler.add(message(2, "b", true)).unwrap();
+        assert_eq!(keys(&sample), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,776 @@
+use std::collections::{HashMap, HashSet};
+use log::{debug, warn};
+use collector_protocol::collector::collector::CollectorMetrics;
+use collector_protocol::metrics::metrics::series_id;
+use crate::mapping::mapping::{EnumValue, Mapper, Prefix, ValueType};
+use crate::telemetry::telemetry::key_value::Value;
+use crate::telemetry::telemetry::OpenConfigData;
+use crate::Path as ConfigPath;
+
+// the state of a series not reported for this many intervals of its path
+// (freq or max_silent_interval, whichever is longer) is dropped
+const EVICT_INTERVALS: u64 = 5;
+// interval of on change paths (freq 0) for eviction, in milliseconds
+const ON_CHANGE_INTERVAL: u64 = 60 * 60 * 1000;
+
+// Converts OpenConfigData messages of a subscription into CollectorMetrics.
+// The converter keeps the previous sample of every prefix to carry forward
+// suppressed counters and the previous value of every series to compute rates.
+pub struct Converter{
+    paths: Vec<(ConfigPath, Mapper)>,
+    namespace: String,
+    // state by system id and sensor path
+    sensors: HashMap<String, Sensor>,
+}
+
+#[derive(Default)]
+struct Sensor{
+    // previous sample by prefix
+    prev_metrics_map: HashMap<String, OpenConfigMetrics>,
+    // previous value and timestamp by counter series
+    prev_values: HashMap<String, (u64, u64)>,
+    // last sample and timestamp of every info metric to zero the series of
+    // a previous value
+    prev_info: HashMap<String, (OpenConfigMetricsData, u64)>,
+}
+
+impl Sensor{
+    // drops the state of series last reported before oldest
+    fn evict(&mut self, oldest: u64){
+        self.prev_metrics_map.retain(|_, m| m.reported >= oldest);
+        self.prev_values.retain(|_, (_, ts)| *ts >= oldest);
+        self.prev_info.retain(|_, (_, ts)| *ts >= oldest);
+    }
+}
+
+impl Converter{
+    pub fn new(paths: &[ConfigPath], namespace: String) -> anyhow::Result<Converter>{
+        let mut path_list = Vec::new();
+        for path in paths{
+            let mapper = Mapper::new(&path.mapping.clone().unwrap_or_default())?;
+            path_list.push((path.clone(), mapper));
+        }
+        Ok(Converter{
+            paths: path_list,
+            namespace,
+            sensors: HashMap::new(),
+        })
+    }
+
+    pub fn convert(&mut self, data: &OpenConfigData) -> Vec<CollectorMetrics>{
+        let mut collector_metrics_list = Vec::new();
+        // the path is <sensor name>:<subscribed path>:<path>:<component>
+        let data_path: Vec<&str> = data.path.split(':').collect();
+        if data_path.len() < 2{
+            return collector_metrics_list;
+        }
+        let Some((path, mapper)) = self.paths.iter().find(|(path, _)| path.path == data_path[1]) else {
+            return collector_metrics_list;
+        };
+        let sensor = self.sensors.entry(format!("{}:{}", data.system_id, data.path)).or_default();
+        let interval = match path.freq.max(path.max_silent_interval) as u64{
+            0 => ON_CHANGE_INTERVAL,
+            interval => interval,
+        };
+        sensor.evict(data.timestamp.saturating_sub(EVICT_INTERVALS * interval));
+        let mut open_config_metrics_list = OpenConfigMetricsList::new();
+        let mut ts = data.timestamp;
+        for kv in &data.kv{
+            if kv.key == "__timestamp__"{
+                if let Some(value) = &kv.value{
+                    ts = convert_value(value);
+                }
+            } else if kv.key == "index" {
+                continue;
+            } else if kv.key == "__prefix__"{
+                if let Some(Value::StrValue(v)) = &kv.value{
+                    open_config_metrics_list.add(mapper.prefix(v), ts, data.system_id.clone(), self.namespace.clone());
+                }
+                //"/cos/interfaces/interface[name='et-0/0/8']/queues/queue[queue='8']/",
+            } else if let Some(value) = &kv.value{
+                let Some(prefix) = open_config_metrics_list.prefix() else {
+                    continue;
+                };
+                let key = mapper.key(&kv.key);
+                let full_path = mapper.full_path(prefix, &key);
+                if !mapper.keep(&full_path){
+                    continue;
+                }
+                let mut metric_name = mapper.metric_name(prefix, &key);
+                let mut value_type = mapper.value_type(&full_path);
+                let mut labels = key.labels.clone();
+                // info metrics get an entry of their own so the value label
+                // does not end up on the other metrics of the key
+                let mut counter_key = series_id("", &key.labels);
+                let Some(sample) = sample(&kv.key, value) else {
+                    continue;
+                };
+                let converted_value = match sample{
+                    Sample::Number(v) => v,
+                    Sample::State(v) => {
+                        value_type = ValueType::Gauge;
+                        v
+                    },
+                    Sample::Text(v) => {
+                        value_type = ValueType::Gauge;
+                        match mapper.enum_value(&full_path, &v){
+                            Some(EnumValue::Mapped(v)) => v,
+                            // the gauge is set to the unknown value rather
+                            // than left at the previous state
+                            Some(EnumValue::Unknown(unknown)) => {
+                                debug!("{}: {} is not in the enum mapping", full_path, v);
+                                unknown
+                            },
+                            None => {
+                                metric_name = format!("{}_info", metric_name);
+                                counter_key = series_id(&metric_name, &key.labels);
+                                labels.insert("value".to_string(), v);
+                                1
+                            },
+                        }
+                    },
+                };
+                let rate = match value_type{
+                    ValueType::Counter => {
+                        let ts = open_config_metrics_list.ts();
+                        let id = series_id(&metric_name, &open_config_metrics_list.labels(&labels));
+                        let rate = match sensor.prev_values.get(&id){
+                            Some((prev_value, prev_ts)) => rate(*prev_value, *prev_ts, converted_value, ts),
+                            None => 0,
+                        };
+                        sensor.prev_values.insert(id, (converted_value, ts));
+                        Some(rate)
+                    },
+                    ValueType::Gauge => None,
+                };
+                open_config_metrics_list.add_counter(counter_key, metric_name, labels, converted_value, rate);
+            }
+        }
+
+        let prev_metrics_map = &mut sensor.prev_metrics_map;
+        if path.suppress_unchanged{
+            for open_config_metrics in &mut open_config_metrics_list.0{
+                if let Some(prev_open_config_metrics) = prev_metrics_map.get(&open_config_metrics.key()){
+                    open_config_metrics.carry_forward(prev_open_config_metrics);
+                }
+            }
+            // a prefix without any change is left out by the device and is
+            // carried forward in full. A sample without EOM may be spread
+            // across messages, its prefixes are only carried forward once
+            // they were not reported for two intervals.
+            let silent = if path.need_eom { 0 } else { 2 * path.freq as u64 };
+            let reported: HashSet<String> = open_config_metrics_list.0.iter().map(|m| m.key()).collect();
+            for (key, prev_open_config_metrics) in prev_metrics_map.iter(){
+                if reported.contains(key) || prev_open_config_metrics.ts + silent > data.timestamp{
+                    continue;
+                }
+                let mut open_config_metrics = OpenConfigMetrics::new(prev_open_config_metrics.prefix.clone(), data.system_id.clone(), self.namespace.clone());
+                open_config_metrics.prefix_labels = prev_open_config_metrics.prefix_labels.clone();
+                open_config_metrics.ts = data.timestamp;
+                open_config_metrics.reported = prev_open_config_metrics.reported;
+                open_config_metrics.carry_forward(prev_open_config_metrics);
+                open_config_metrics_list.0.push(open_config_metrics);
+            }
+        }
+        for open_config_metrics in open_config_metrics_list.0{
+            for (counter_key, open_config_metrics_data) in &open_config_metrics.metrics_data{
+                // the series of the previous value of an info metric is
+                // zeroed, so only the current value is 1
+                if open_config_metrics_data.is_info(){
+                    let info_key = format!("{}{}", open_config_metrics.key(), counter_key);
+                    if let Some((prev_data, _)) = sensor.prev_info.insert(info_key, (open_config_metrics_data.clone(), open_config_metrics.ts)){
+                        if prev_data.metrics_labels.get("value") != open_config_metrics_data.metrics_labels.get("value"){
+                            collector_metrics_list.push(CollectorMetrics{
+                                namespace: None,
+                                labels: open_config_metrics.labels(&prev_data.metrics_labels),
+                                metrics: prev_data.data.keys().map(|name| (name.clone(), 0)).collect(),
+                            });
+                        }
+                    }
+                }
+                let labels_map = open_config_metrics.labels(&open_config_metrics_data.metrics_labels);
+                collector_metrics_list.push(CollectorMetrics{
+                    namespace: None,
+                    labels: labels_map,
+                    metrics: open_config_metrics_data.data.clone(),
+                });
+            }
+            sensor.prev_metrics_map.insert(open_config_metrics.key(), open_config_metrics);
+        }
+        collector_metrics_list
+    }
+}
+
+// per second rate of a counter, a value lower than the previous one is
+// treated as a counter reset or wrap and the new value is taken as delta
+fn rate(prev_value: u64, prev_ts: u64, value: u64, ts: u64) -> u64{
+    let data_delta = if value >= prev_value{
+        value - prev_value
+    } else {
+        value
+    };
+    if data_delta == 0 || ts <= prev_ts{
+        return 0;
+    }
+    let per_sec_factor = (ts - prev_ts) as f64 / 1_000.0;
+    (data_delta as f64 / per_sec_factor) as u64
+}
+
+#[derive(Clone, Debug)]
+struct OpenConfigMetricsData{
+    data: HashMap<String, u64>,
+    metrics_labels: HashMap<String, String>,
+}
+
+impl OpenConfigMetricsData{
+    pub fn new() -> Self{
+        Self{
+            data: HashMap::new(),
+            metrics_labels: HashMap::new(),
+        }
+    }
+    pub fn add(&mut self, key: String, value: u64, rate: Option<u64>){
+        if let Some(rate) = rate{
+            self.data.insert(format!("{}_per_sec", key), rate);
+        }
+        self.data.insert(key, value);
+    }
+    pub fn add_labels(&mut self, labels: HashMap<String, String>){
+        for (k,v) in labels{
+            self.metrics_labels.insert(k, v);
+        }
+    }
+    pub fn is_info(&self) -> bool{
+        self.data.keys().any(|name| name.ends_with("_info"))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OpenConfigMetrics{
+    prefix: Prefix,
+    metrics_data: HashMap<String,OpenConfigMetricsData>,
+    prefix_labels: HashMap<String, String>,
+    ts: u64,
+    // timestamp the device last reported the prefix, ts of a prefix
+    // carried forward in full is the one of the carrying sample
+    reported: u64,
+    system_id: String,
+    namespace: String,
+}
+
+impl OpenConfigMetrics{
+    pub fn new(prefix: Prefix, system_id: String, namespace: String) -> Self{
+        Self{
+            prefix,
+            metrics_data: HashMap::new(),
+            prefix_labels: HashMap::new(),
+            ts: 0,
+            reported: 0,
+            system_id,
+            namespace,
+        }
+    }
+    pub fn add_metrics_data(&mut self, counter_key: String, counter_name: String, value: u64, labels: HashMap<String, String>, rate: Option<u64>){
+        if let Some(data) = self.metrics_data.get_mut(&counter_key){
+            data.add(counter_name, value, rate);
+            data.add_labels(labels);
+        } else {
+            let mut data = OpenConfigMetricsData::new();
+            data.add(counter_name, value, rate);
+            data.add_labels(labels);
+            self.metrics_data.insert(counter_key, data);
+        }
+    }
+
+    // counters suppressed by the device because they did not change are
+    // taken over from the previous sample with a rate of 0, so their rate
+    // does not get stuck at the last non-zero value
+    pub fn carry_forward(&mut self, prev: &OpenConfigMetrics){
+        for (counter_key, prev_data) in &prev.metrics_data{
+            let data = self.metrics_data.entry(counter_key.clone()).or_insert_with(OpenConfigMetricsData::new);
+            for (name, value) in &prev_data.data{
+                if name.ends_with("_per_sec") || data.data.contains_key(name){
+                    continue;
+                }
+                let rate = prev_data.data.contains_key(&format!("{}_per_sec", name)).then_some(0);
+                data.add(name.clone(), *value, rate);
+            }
+            for (k, v) in &prev_data.metrics_labels{
+                data.metrics_labels.entry(k.clone()).or_insert(v.clone());
+            }
+        }
+    }
+
+    // labels of a series of the prefix with the given counter labels
+    pub fn labels(&self, counter_labels: &HashMap<String, String>) -> HashMap<String, String>{
+        let mut labels = self.prefix_labels.clone();
+        labels.extend(counter_labels.clone());
+        labels.insert("namespace".to_string(), self.namespace.clone());
+        labels.insert("system_id".to_string(), self.system_id.clone());
+        labels
+    }
+
+    pub fn key(&self) -> String{
+        series_id(&self.prefix.name, &self.labels(&HashMap::new()))
+    }
+}
+
+#[derive(Debug)]
+struct OpenConfigMetricsList(Vec<OpenConfigMetrics>);
+
+impl OpenConfigMetricsList{
+    pub fn new() -> Self{
+        Self(Vec::new())
+    }
+    pub fn add(&mut self, prefix: Prefix, ts: u64, system_id: String, namespace: String){
+        let prefix_labels = prefix.labels.clone();
+        let mut metrics = OpenConfigMetrics::new(prefix, system_id, namespace);
+        metrics.prefix_labels = prefix_labels;
+        metrics.ts = ts;
+        metrics.reported = ts;
+        self.0.push(metrics);
+    }
+    pub fn add_counter(&mut self, counter_key: String, metric_name: String, counter_labels: HashMap<String, String>, value: u64, rate: Option<u64>){
+        if let Some(last_open_config_metrix) = self.0.last_mut(){
+            last_open_config_metrix.add_metrics_data(counter_key, metric_name, value, counter_labels, rate);
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&Prefix>{
+        self.0.last().map(|m| &m.prefix)
+    }
+
+    pub fn labels(&self, counter_labels: &HashMap<String, String>) -> HashMap<String, String>{
+        self.0.last().map(|m| m.labels(counter_labels)).unwrap_or_default()
+    }
+
+    pub fn ts(&self) -> u64{
+        self.0.last().unwrap().ts
+    }
+}
+
+// a KeyValue value as exported
+enum Sample{
+    Number(u64),
+    // a numeric state which is never a counter, e.g. a boolean
+    State(u64),
+    // a string state, mapped by the enum mappings or exported as info metric
+    Text(String),
+}
+
+// metric values are unsigned, negative values are dropped
+fn sample(key: &str, value: &Value) -> Option<Sample>{
+    match value{
+        Value::BoolValue(v) => Some(Sample::State(*v as u64)),
+        Value::StrValue(v) => Some(Sample::Text(v.clone())),
+        Value::BytesValue(v) => Some(Sample::Text(String::from_utf8_lossy(v).to_string())),
+        Value::IntValue(v) | Value::SintValue(v) if *v < 0 => negative(key, v),
+        Value::DoubleValue(v) if *v < 0.0 => negative(key, v),
+        Value::FloatValue(v) if *v < 0.0 => negative(key, v),
+        _ => Some(Sample::Number(convert_value(value))),
+    }
+}
+
+fn negative(key: &str, value: impl std::fmt::Display) -> Option<Sample>{
+    warn!("Dropping negative value {} of {}", value, key);
+    None
+}
+
+fn convert_value(value: &Value) -> u64{
+    match value{
+        Value::DoubleValue(v) => {
+            *v as u64
+        },
+        Value::FloatValue(v) => {
+            *v as u64
+        },
+        Value::UintValue(v) => {
+            *v
+        },
+        Value::IntValue(v) => {
+            *v as u64
+        },
+        Value::SintValue(v) => {
+            *v as u64
+        },
+        Value::BoolValue(v) => {
+            *v as u64
+        },
+        _ => {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::BTreeMap;
+    use super::*;
+    use crate::telemetry::telemetry::KeyValue;
+
+    // golden files in tests/fixtures hold a sequence of Junos messages and
+    // the metrics expected after converting each of them, worked out by hand
+    // and never generated by the converter
+    #[derive(serde::Deserialize)]
+    struct Fixture{
+        paths: Vec<ConfigPath>,
+        messages: Vec<Message>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Message{
+        data: Data,
+        expected: Vec<Expected>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Data{
+        system_id: String,
+        #[serde(default)]
+        component_id: u32,
+        path: String,
+        sequence_number: u64,
+        #[serde(default)]
+        timestamp: u64,
+        kv: Vec<Kv>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Kv{
+        key: String,
+        uint: Option<u64>,
+        int: Option<i64>,
+        double: Option<f64>,
+        bool: Option<bool>,
+        str: Option<String>,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Expected{
+        labels: BTreeMap<String, String>,
+        metrics: BTreeMap<String, u64>,
+    }
+
+    impl From<Data> for OpenConfigData{
+        fn from(data: Data) -> Self{
+            OpenConfigData{
+                system_id: data.system_id,
+                component_id: data.component_id,
+                path: data.path,
+                sequence_number: data.sequence_number,
+                timestamp: data.timestamp,
+                kv: data.kv.into_iter().map(|kv| KeyValue{
+                    value: kv.uint.map(Value::UintValue)
+                        .or(kv.int.map(Value::IntValue))
+                        .or(kv.double.map(Value::DoubleValue))
+                        .or(kv.bool.map(Value::BoolValue))
+                        .or(kv.str.map(Value::StrValue)),
+                    key: kv.key,
+                }).collect(),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn run_fixture(fixture: &str){
+        let fixture: Fixture = serde_yaml::from_str(fixture).unwrap();
+        let mut converter = Converter::new(&fixture.paths, "qfx".to_string()).unwrap();
+        for (idx, message) in fixture.messages.into_iter().enumerate(){
+            let mut got: Vec<Expected> = converter.convert(&message.data.into()).into_iter()
+                .map(|m| Expected{
+                    labels: m.labels.into_iter().collect(),
+                    metrics: m.metrics.into_iter().collect(),
+                })
+                .collect();
+            got.sort();
+            let mut expected = message.expected;
+            expected.sort();
+            assert_eq!(got, expected, "message {}", idx);
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_timestamp(){
+        run_fixture(include_str!("../../tests/fixtures/interfaces.yaml"));
+    }
+
+    #[test]
+    fn test_bracket_keys(){
+        run_fixture(include_str!("../../tests/fixtures/queues.yaml"));
+    }
+
+    #[test]
+    fn test_counter_wrap(){
+        run_fixture(include_str!("../../tests/fixtures/counter_wrap.yaml"));
+    }
+
+    #[test]
+    fn test_qualified_mapping(){
+        run_fixture(include_str!("../../tests/fixtures/qualified.yaml"));
+    }
+
+    #[test]
+    fn test_states(){
+        run_fixture(include_str!("../../tests/fixtures/states.yaml"));
+    }
+
+    #[test]
+    fn test_unknown_path(){
+        let paths: Vec<ConfigPath> = serde_yaml::from_str("- path: /interfaces/\n  freq: 1000").unwrap();
+        let mut converter = Converter::new(&paths, "qfx".to_string()).unwrap();
+        let data = OpenConfigData{
+            path: "sensor_1000:/components/:/components/:PFE".to_string(),
+            ..Default::default()
+        };
+        assert!(converter.convert(&data).is_empty());
+    }
+
+    #[test]
+    fn test_rate(){
+        assert_eq!(rate(100, 1000, 300, 3000), 100);
+        assert_eq!(rate(100, 1000, 100, 3000), 0);
+        assert_eq!(rate(100, 1000, 300, 1000), 0);
+        assert_eq!(rate(500, 1000, 50, 2000), 50);
+    }
+
+    // a prefix the device leaves out because nothing changed keeps its value
+    // with a rate of 0, right away for complete samples and after two
+    // intervals for samples which may be spread across messages
+    #[test]
+    fn test_suppressed_prefix(){
+        run_fixture(r#"
+paths:
+  - path: /interfaces/
+    freq: 2000
+    suppress_unchanged: true
+messages:
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 1
+      timestamp: 1000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/0']/"
+        - key: state/counters/in-pkts
+          uint: 100
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 1000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 1000, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 2
+      timestamp: 3000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 5000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 5000, __interfaces__interface___state__counters__in_pkts_per_sec: 2000}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 3
+      timestamp: 5000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 9000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 9000, __interfaces__interface___state__counters__in_pkts_per_sec: 2000}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 4
+      timestamp: 5000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/0']/"
+        - key: state/counters/in-pkts
+          uint: 4100
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 4100, __interfaces__interface___state__counters__in_pkts_per_sec: 1000}
+"#);
+        run_fixture(r#"
+paths:
+  - path: /interfaces/
+    freq: 2000
+    suppress_unchanged: true
+    need_eom: true
+messages:
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 1
+      timestamp: 1000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/0']/"
+        - key: state/counters/in-pkts
+          uint: 100
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 1000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 1000, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 2
+      timestamp: 3000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 5000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 5000, __interfaces__interface___state__counters__in_pkts_per_sec: 2000}
+"#);
+    }
+
+    // a prefix not reported for five intervals is forgotten, it is no longer
+    // carried forward and its next rate starts over
+    #[test]
+    fn test_evict(){
+        run_fixture(r#"
+paths:
+  - path: /interfaces/
+    freq: 1000
+    suppress_unchanged: true
+    need_eom: true
+messages:
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 1
+      timestamp: 1000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/0']/"
+        - key: state/counters/in-pkts
+          uint: 100
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 1000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 1000, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 2
+      timestamp: 2000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 2000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 100, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 2000, __interfaces__interface___state__counters__in_pkts_per_sec: 1000}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 3
+      timestamp: 7000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/1']/"
+        - key: state/counters/in-pkts
+          uint: 3000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 3000, __interfaces__interface___state__counters__in_pkts_per_sec: 200}
+  - data:
+      system_id: qfx1
+      path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+      sequence_number: 4
+      timestamp: 8000
+      kv:
+        - key: __prefix__
+          str: "/interfaces/interface[name='et-0/0/0']/"
+        - key: state/counters/in-pkts
+          uint: 600
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/0}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 600, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_name: et_0/0/1}
+        metrics: {__interfaces__interface___state__counters__in_pkts: 3000, __interfaces__interface___state__counters__in_pkts_per_sec: 0}
+"#);
+    }
+
+    // every converter gets differently seeded label maps, the rates must
+    // not depend on it
+    #[test]
+    fn test_stable_rates(){
+        let fixture = r#"
+paths:
+  - path: /qos/interfaces/
+    freq: 2000
+messages:
+  - data:
+      system_id: qfx1
+      path: "sensor_1002:/qos/interfaces/:/qos/interfaces/:PFE"
+      sequence_number: 1
+      timestamp: 1000
+      kv:
+        - key: __prefix__
+          str: "/qos/interfaces/interface[interface-id='et-0/0/8'][unit='0'][direction='out']/"
+        - key: "queue[queue-id='3'][priority='low']/pkts"
+          uint: 100
+        - key: "queue[queue-id='low'][priority='3']/pkts"
+          uint: 1000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_interface_id: et_0/0/8, prefix_unit: "0", prefix_direction: out, counter_queue_id: "3", counter_priority: low}
+        metrics: {__qos__interfaces__interface___queue__pkts: 100, __qos__interfaces__interface___queue__pkts_per_sec: 0}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_interface_id: et_0/0/8, prefix_unit: "0", prefix_direction: out, counter_queue_id: low, counter_priority: "3"}
+        metrics: {__qos__interfaces__interface___queue__pkts: 1000, __qos__interfaces__interface___queue__pkts_per_sec: 0}
+  - data:
+      system_id: qfx1
+      path: "sensor_1002:/qos/interfaces/:/qos/interfaces/:PFE"
+      sequence_number: 2
+      timestamp: 3000
+      kv:
+        - key: __prefix__
+          str: "/qos/interfaces/interface[interface-id='et-0/0/8'][unit='0'][direction='out']/"
+        - key: "queue[queue-id='3'][priority='low']/pkts"
+          uint: 300
+        - key: "queue[queue-id='low'][priority='3']/pkts"
+          uint: 5000
+    expected:
+      - labels: {namespace: qfx, system_id: qfx1, prefix_interface_id: et_0/0/8, prefix_unit: "0", prefix_direction: out, counter_queue_id: "3", counter_priority: low}
+        metrics: {__qos__interfaces__interface___queue__pkts: 300, __qos__interfaces__interface___queue__pkts_per_sec: 100}
+      - labels: {namespace: qfx, system_id: qfx1, prefix_interface_id: et_0/0/8, prefix_unit: "0", prefix_direction: out, counter_queue_id: low, counter_priority: "3"}
+        metrics: {__qos__interfaces__interface___queue__pkts: 5000, __qos__interfaces__interface___queue__pkts_per_sec: 2000}
+"#;
+        for _ in 0..32{
+            run_fixture(fixture);
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod converter;
+pub mod assembler;
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// A secret is either given inline, read from an environment variable or
+// read from a file, e.g. a docker or kubernetes secret mount:
+//   password: PWD
+//   password: { env: JUNOS_PASSWORD }
+//   password: { file: /run/secrets/junos_password }
+// The content of a file is used as is except for the line breaks at its end,
+// surrounding spaces are part of the secret. The values are sent as gRPC
+// metadata, secrets with characters which aren't valid there (control
+// characters such as inner line breaks, non-ASCII) fail the subscription.
+#[derive(serde::Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Secret{
+    Env{ env: String },
+    File{ file: String },
+    Plain(String),
+}
+
+impl Secret{
+    pub fn resolve(&self) -> anyhow::Result<String>{
+        match self{
+            Secret::Plain(v) => Ok(v.clone()),
+            Secret::Env{ env } => std::env::var(env).map_err(|e| anyhow::anyhow!("Failed to read environment variable {}: {}", env, e)),
+            Secret::File{ file } => {
+                let v = std::fs::read_to_string(file).map_err(|e| anyhow::anyhow!("Failed to read secret file {}: {}", file, e))?;
+                Ok(v.trim_end_matches(['\r', '\n']).to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Secret{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            Secret::Plain(_) => write!(f, "Plain(***)"),
+            Secret::Env{ env } => write!(f, "Env({})", env),
+            Secret::File{ file } => write!(f, "File({})", file),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Credentials{
+    pub user: Option<Secret>,
+    pub password: Option<Secret>,
+}
+
+// credentials file referenced by the config, devices are looked up by address
+// and fall back to the default entry:
+//   default:
+//     user: { env: JUNOS_USER }
+//     password: { file: /run/secrets/junos_password }
+//   devices:
+//     10.0.0.1:50051:
+//       password: { env: SPINE1_PASSWORD }
+#[derive(serde::Deserialize, Debug, Default)]
+pub struct CredentialsFile{
+    pub default: Option<Credentials>,
+    pub devices: Option<HashMap<String, Credentials>>,
+}
+
+impl CredentialsFile{
+    pub fn load(path: &str) -> anyhow::Result<CredentialsFile>{
+        let file = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read credentials file {}: {}", path, e))?;
+        let file: CredentialsFile = serde_yaml::from_str(&file)?;
+        Ok(file)
+    }
+
+    // resolves user and password for a device, values set on the device take
+    // precedence over the device entry which takes precedence over the default
+    pub fn resolve(&self, address: &str, device: &Credentials) -> anyhow::Result<(String, String)>{
+        let entry = self.devices.as_ref().and_then(|d| d.get(address));
+        let default = self.default.as_ref();
+        let user = device.user.as_ref()
+            .or(entry.and_then(|e| e.user.as_ref()))
+            .or(default.and_then(|d| d.user.as_ref()))
+            .ok_or(anyhow::anyhow!("No user configured for {}", address))?;
+        let password = device.password.as_ref()
+            .or(entry.and_then(|e| e.password.as_ref()))
+            .or(default.and_then(|d| d.password.as_ref()))
+            .ok_or(anyhow::anyhow!("No password configured for {}", address))?;
+        Ok((user.resolve()?, password.resolve()?))
+    }
+}
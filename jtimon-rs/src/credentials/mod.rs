@@ -0,0 +1 @@
+pub mod credentials;
\ No newline at end of file
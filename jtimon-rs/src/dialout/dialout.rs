@@ -0,0 +1,503 @@
+use std::sync::Arc;
+use log::{error, info, warn};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+use collector_protocol::sender::sender::Client as CollClient;
+use crate::connection::connection::read_file;
+use crate::converter::converter::Converter;
+use crate::gnmi::dialout::{subscriber_server::{Subscriber, SubscriberServer}, DialOutRequest, DialOutResponse};
+use crate::gnmi::gnmi::{
+    subscribe_request, subscribe_response, subscription_list, typed_value, Encoding, Notification, PathElem,
+    SubscribeRequest, Subscription, SubscriptionList, SubscriptionMode,
+};
+use crate::gnmi_client::gnmi_client::parse_path;
+use crate::recorder::recorder::Client as RecClient;
+use crate::recording::recording::Source;
+use crate::telemetry::telemetry::{key_value::Value, KeyValue, OpenConfigData};
+use crate::{DialOut, DialOutDevice, Path};
+
+// Accepts Junos gRPC dial-out (dialout.Subscriber). Devices must present a
+// client certificate signed by the configured CA, the common name or a DNS
+// subject alternative name of the certificate selects the device entry with
+// the namespace and paths used to convert its data. The paths are sent to
+// the device as a gNMI subscription once it connected.
+pub struct DialOutServer{
+    config: DialOut,
+    collector_client: CollClient,
+    recorder: RecClient,
+}
+
+impl DialOutServer{
+    pub fn new(config: DialOut, collector_client: CollClient, recorder: RecClient) -> DialOutServer{
+        DialOutServer{
+            config,
+            collector_client,
+            recorder,
+        }
+    }
+
+    pub async fn run(self) -> anyhow::Result<()>{
+        let tls = &self.config.tls;
+        let (Some(cert_file), Some(key_file), Some(ca_file)) = (&tls.cert_file, &tls.key_file, &tls.ca_file) else {
+            return Err(anyhow::anyhow!("dialout requires cert_file, key_file and ca_file"));
+        };
+        let tls_config = ServerTlsConfig::new()
+            .identity(Identity::from_pem(read_file(cert_file)?, read_file(key_file)?))
+            .client_ca_root(Certificate::from_pem(read_file(ca_file)?));
+        let service = DialOutService{
+            devices: Arc::new(self.config.devices.clone()),
+            collector_client: self.collector_client.clone(),
+            recorder: self.recorder.clone(),
+        };
+        info!("Dial-out server listening on {}", self.config.address);
+        Server::builder()
+            .tls_config(tls_config)?
+            .add_service(SubscriberServer::new(service))
+            .serve(self.config.address.parse()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct DialOutService{
+    devices: Arc<Vec<DialOutDevice>>,
+    collector_client: CollClient,
+    recorder: RecClient,
+}
+
+impl DialOutService{
+    #[allow(clippy::result_large_err)]
+    fn device<T>(&self, request: &Request<T>) -> Result<DialOutDevice, Status>{
+        let certs = request.peer_certs().ok_or_else(|| Status::unauthenticated("client certificate required"))?;
+        let cert = certs.first().ok_or_else(|| Status::unauthenticated("client certificate required"))?;
+        let names = certificate_names(cert.get_ref())
+            .map_err(|e| Status::unauthenticated(format!("invalid client certificate: {}", e)))?;
+        self.devices.iter()
+            .find(|d| names.contains(&d.name))
+            .cloned()
+            .ok_or_else(|| {
+                warn!("Rejecting dial-out from unknown device {:?}", names);
+                Status::permission_denied(format!("no device configured for {:?}", names))
+            })
+    }
+}
+
+#[tonic::async_trait]
+impl Subscriber for DialOutService{
+    type DialOutSubscriberStream = ReceiverStream<Result<DialOutRequest, Status>>;
+
+    async fn dial_out_subscriber(&self, request: Request<Streaming<DialOutResponse>>) -> Result<Response<Self::DialOutSubscriberStream>, Status>{
+        let device = self.device(&request)?;
+        let mut converter = Converter::new(&device.paths, device.namespace.clone())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let collector_client = self.collector_client.clone();
+        let recorder = self.recorder.clone();
+        let mut stream = request.into_inner();
+        // the request stream ends when the task drops tx
+        let (tx, rx) = mpsc::channel(1);
+        tx.send(Ok(DialOutRequest{
+            request: Some(subscribe_request(&device.paths)),
+        })).await.map_err(|e| Status::internal(e.to_string()))?;
+        tokio::spawn(async move{
+            info!("Dial-out from {}", device.name);
+            while let Some(res) = stream.next().await{
+                let dial_out_response = match res{
+                    Ok(dial_out_response) => dial_out_response,
+                    Err(e) => {
+                        error!("Failed to receive from {}: {:?}", device.name, e);
+                        break;
+                    }
+                };
+                for response in dial_out_response.response{
+                    recorder.subscribe_response(Source::Dialout, &device.name, &response);
+                    let Some(subscribe_response::Response::Update(notification)) = response.response else {
+                        continue;
+                    };
+                    // the device is the one of the certificate, a target
+                    // naming another one ends the stream
+                    if let Some(target) = notification.prefix.as_ref().map(|p| &p.target).filter(|t| !t.is_empty() && **t != device.name){
+                        warn!("Closing dial-out from {}, it sent data of {}", device.name, target);
+                        let _ = tx.send(Err(Status::permission_denied(format!("target {} is not {}", target, device.name)))).await;
+                        return;
+                    }
+                    for data in notification_data(&notification, &device.name, &device.paths){
+                        for collector_metrics in converter.convert(&data){
+                            if let Err(e) = collector_client.send(collector_metrics).await{
+                                error!("Failed to send metrics: {:?}", e);
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+            info!("Dial-out from {} closed", device.name);
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+// the subscription of the configured paths, sampled every freq
+// milliseconds or on change for a freq of 0
+fn subscribe_request(paths: &[Path]) -> SubscribeRequest{
+    let subscription = paths.iter().map(|p| {
+        let mut path = parse_path(&p.path);
+        if let Some(origin) = &p.origin{
+            path.origin = origin.clone();
+        }
+        let mode = match p.freq{
+            0 => SubscriptionMode::OnChange,
+            _ => SubscriptionMode::Sample,
+        };
+        // gNMI intervals are in nanoseconds
+        Subscription{
+            path: Some(path),
+            mode: mode as i32,
+            sample_interval: p.freq as u64 * 1_000_000,
+            suppress_redundant: p.suppress_unchanged,
+            heartbeat_interval: p.max_silent_interval as u64 * 1_000_000,
+        }
+    }).collect();
+    SubscribeRequest{
+        request: Some(subscribe_request::Request::Subscribe(SubscriptionList{
+            subscription,
+            mode: subscription_list::Mode::Stream as i32,
+            encoding: Encoding::Proto as i32,
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+// common name and DNS subject alternative names of a DER certificate
+fn certificate_names(der: &[u8]) -> anyhow::Result<Vec<String>>{
+    let (_, cert) = X509Certificate::from_der(der)?;
+    let mut names = Vec::new();
+    for cn in cert.subject().iter_common_name(){
+        if let Ok(cn) = cn.as_str(){
+            names.push(cn.to_string());
+        }
+    }
+    if let Ok(Some(san)) = cert.subject_alternative_name(){
+        for name in &san.value.general_names{
+            if let GeneralName::DNSName(name) = name{
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+// Turns a gNMI notification into the key/value layout of the Junos sensors
+// so it goes through the same converter and mappings: the path of an update
+// without its last element becomes the __prefix__, the last element the key.
+// Updates are grouped by the longest configured path their path starts with,
+// updates without a configured path are dropped.
+pub fn notification_data(notification: &Notification, system_id: &str, paths: &[Path]) -> Vec<OpenConfigData>{
+    let mut data_list: Vec<OpenConfigData> = Vec::new();
+    for update in &notification.update{
+        let mut elem: Vec<&PathElem> = notification.prefix.iter().flat_map(|p| p.elem.iter()).collect();
+        elem.extend(update.path.iter().flat_map(|p| p.elem.iter()));
+        let Some((leaf, parents)) = elem.split_last() else {
+            continue;
+        };
+        let Some(value) = update.val.as_ref().and_then(|v| v.value.as_ref()).and_then(kv_value) else {
+            continue;
+        };
+        let plain_path = format!("/{}", elem.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>().join("/"));
+        let Some(path) = paths.iter()
+            .filter(|p| path_matches(&plain_path, &p.path))
+            .max_by_key(|p| p.path.len()) else {
+            continue;
+        };
+        let prefix = format!("{}/", parents.iter().map(|e| format_elem(e)).collect::<String>());
+        let data_path = format!("gnmi:{}:{}:", path.path, path.path);
+        let data = match data_list.iter_mut().find(|d| d.path == data_path){
+            Some(data) => data,
+            None => {
+                data_list.push(OpenConfigData{
+                    system_id: system_id.to_string(),
+                    path: data_path,
+                    // gNMI timestamps are in nanoseconds
+                    timestamp: (notification.timestamp / 1_000_000) as u64,
+                    ..Default::default()
+                });
+                data_list.last_mut().unwrap()
+            }
+        };
+        let last_prefix = data.kv.iter().rev().find(|kv| kv.key == "__prefix__").and_then(|kv| kv.value.clone());
+        if last_prefix != Some(Value::StrValue(prefix.clone())){
+            data.kv.push(KeyValue{
+                key: "__prefix__".to_string(),
+                value: Some(Value::StrValue(prefix)),
+            });
+        }
+        data.kv.push(KeyValue{
+            key: format_elem(leaf).trim_start_matches('/').to_string(),
+            value: Some(value),
+        });
+    }
+    data_list
+}
+
+// a configured path matches the paths it is a prefix of up to a path
+// element boundary, /interfaces matches /interfaces/interface but not
+// /interfaces-extra
+fn path_matches(path: &str, configured: &str) -> bool{
+    path.strip_prefix(configured.trim_end_matches('/'))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with('['))
+}
+
+// /name[key='value'] with the keys sorted
+fn format_elem(elem: &PathElem) -> String{
+    let mut keys: Vec<(&String, &String)> = elem.key.iter().collect();
+    keys.sort();
+    let keys: String = keys.into_iter().map(|(k, v)| format!("[{}='{}']", k, v)).collect();
+    format!("/{}{}", elem.name, keys)
+}
+
+fn kv_value(value: &typed_value::Value) -> Option<Value>{
+    match value{
+        typed_value::Value::StringVal(v) | typed_value::Value::AsciiVal(v) => Some(Value::StrValue(v.clone())),
+        typed_value::Value::IntVal(v) => Some(Value::IntValue(*v)),
+        typed_value::Value::UintVal(v) => Some(Value::UintValue(*v)),
+        typed_value::Value::BoolVal(v) => Some(Value::BoolValue(*v)),
+        typed_value::Value::BytesVal(v) => Some(Value::BytesValue(v.clone())),
+        typed_value::Value::FloatVal(v) => Some(Value::FloatValue(*v)),
+        typed_value::Value::DoubleVal(v) => Some(Value::DoubleValue(*v)),
+        typed_value::Value::DecimalVal(v) => Some(Value::DoubleValue(v.digits as f64 / 10f64.powi(v.precision as i32))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use super::*;
+    use crate::connection::connection::connect;
+    use crate::gnmi::dialout::subscriber_client::SubscriberClient;
+    use crate::gnmi::gnmi::{SubscribeResponse, TypedValue, Update};
+    use crate::Tls;
+
+    // a CA with the server certificate and the certificates of devices mx1
+    // and mx2, written to a temporary directory
+    struct Pki{
+        dir: PathBuf,
+    }
+
+    impl Pki{
+        fn new(name: &str) -> Pki{
+            let dir = std::env::temp_dir().join(format!("jtimon-dialout-{}-{}", std::process::id(), name));
+            std::fs::create_dir_all(&dir).unwrap();
+            let ca_key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = params.self_signed(&ca_key).unwrap();
+            std::fs::write(dir.join("ca.crt"), ca.pem()).unwrap();
+            for name in ["localhost", "mx1", "mx2"]{
+                let key = KeyPair::generate().unwrap();
+                let cert = CertificateParams::new(vec![name.to_string()]).unwrap().signed_by(&key, &ca, &ca_key).unwrap();
+                std::fs::write(dir.join(format!("{}.crt", name)), cert.pem()).unwrap();
+                std::fs::write(dir.join(format!("{}.key", name)), key.serialize_pem()).unwrap();
+            }
+            Pki{ dir }
+        }
+
+        fn path(&self, file: &str) -> Option<String>{
+            Some(self.dir.join(file).to_string_lossy().to_string())
+        }
+
+        fn tls(&self, name: &str) -> Tls{
+            Tls{
+                cert_file: self.path(&format!("{}.crt", name)),
+                key_file: self.path(&format!("{}.key", name)),
+                ca_file: self.path("ca.crt"),
+                server_name: Some("localhost".to_string()),
+                insecure_skip_verify: false,
+            }
+        }
+    }
+
+    impl Drop for Pki{
+        fn drop(&mut self){
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    // runs a dial-out server for mx1 and returns its address and the
+    // receiver of the converted metrics
+    async fn start_server(pki: &Pki) -> (String, mpsc::Receiver<collector_protocol::collector::collector::CollectorMetrics>){
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let config = DialOut{
+            address: address.clone(),
+            tls: pki.tls("localhost"),
+            devices: serde_yaml::from_str(r#"
+- name: mx1
+  namespace: mx
+  paths:
+  - path: /interfaces/
+    freq: 1000
+    mapping:
+      labels: qualified
+      name_template: "{path}"
+"#).unwrap(),
+        };
+        let (tx, rx) = mpsc::channel(16);
+        let server = DialOutServer::new(config, CollClient::new(tx, String::new()), RecClient::disabled());
+        tokio::spawn(server.run());
+        (address, rx)
+    }
+
+    // connects as a device, retrying until the server listens
+    async fn dial(address: &str, tls: &Tls) -> SubscriberClient<tonic::transport::Channel>{
+        for _ in 0..50{
+            if let Ok(channel) = connect(address, Some(tls)).await{
+                return SubscriberClient::new(channel);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("dial-out server at {} not listening", address);
+    }
+
+    fn dial_out_response(target: &str) -> DialOutResponse{
+        let mut prefix = parse_path("/interfaces/interface[name=et-0/0/0]/state");
+        prefix.target = target.to_string();
+        DialOutResponse{
+            response: vec![SubscribeResponse{
+                response: Some(subscribe_response::Response::Update(Notification{
+                    timestamp: 2_000_000_000,
+                    prefix: Some(prefix),
+                    update: vec![update("counters/in-octets", typed_value::Value::UintVal(100))],
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }],
+        }
+    }
+
+    fn update(path: &str, value: typed_value::Value) -> Update{
+        Update{
+            path: Some(parse_path(path)),
+            val: Some(TypedValue{
+                value: Some(value),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_notification_data(){
+        let paths: Vec<Path> = serde_yaml::from_str(r#"
+- path: /interfaces/
+  freq: 0
+  mapping:
+    labels: qualified
+    name_template: "{path}"
+"#).unwrap();
+        let notification = Notification{
+            timestamp: 2_000_000_000,
+            prefix: Some(parse_path("/interfaces/interface[name=et-0/0/0]/state")),
+            update: vec![
+                update("counters/in-octets", typed_value::Value::UintVal(100)),
+                update("oper-status", typed_value::Value::StringVal("UP".to_string())),
+                update("/other", typed_value::Value::UintVal(1)),
+            ],
+            ..Default::default()
+        };
+        let data_list = notification_data(&notification, "qfx1", &paths);
+        assert_eq!(data_list.len(), 1);
+        assert_eq!(data_list[0].timestamp, 2000);
+        let other = Notification{
+            update: vec![update("/interfaces-extra/count", typed_value::Value::UintVal(1))],
+            ..Default::default()
+        };
+        assert!(notification_data(&other, "qfx1", &paths).is_empty());
+        let mut converter = Converter::new(&paths, "qfx".to_string()).unwrap();
+        let metrics = converter.convert(&data_list[0]);
+        let counters = metrics.iter().find(|m| m.metrics.contains_key("interfaces_interface_state_counters_in_octets")).unwrap();
+        assert_eq!(counters.labels.get("interface_name"), Some(&"et-0/0/0".to_string()));
+        assert_eq!(counters.labels.get("system_id"), Some(&"qfx1".to_string()));
+        let info: HashMap<String, u64> = metrics.into_iter().flat_map(|m| m.metrics).collect();
+        assert_eq!(info.get("interfaces_interface_state_oper_status_info"), Some(&1));
+    }
+
+    #[test]
+    fn test_path_matches(){
+        assert!(path_matches("/interfaces", "/interfaces/"));
+        assert!(path_matches("/interfaces/interface/state", "/interfaces/"));
+        assert!(path_matches("/interfaces/interface/state", "/interfaces/interface"));
+        assert!(path_matches("/interfaces[name=et-0/0/0]", "/interfaces"));
+        assert!(!path_matches("/interfaces-extra/count", "/interfaces/"));
+        assert!(!path_matches("/interfaces/interface/state", "/interfaces/inter"));
+    }
+
+    #[test]
+    fn test_subscribe_request(){
+        let paths: Vec<Path> = serde_yaml::from_str(r#"
+- path: /interfaces/
+  freq: 2000
+  suppress_unchanged: true
+  max_silent_interval: 60000
+- path: /system/state/hostname
+  freq: 0
+  origin: openconfig
+"#).unwrap();
+        let Some(subscribe_request::Request::Subscribe(list)) = subscribe_request(&paths).request else {
+            panic!("not a subscription");
+        };
+        assert_eq!(list.mode(), subscription_list::Mode::Stream);
+        assert_eq!(list.subscription[0].path, Some(parse_path("/interfaces/")));
+        assert_eq!(list.subscription[0].mode(), SubscriptionMode::Sample);
+        assert_eq!(list.subscription[0].sample_interval, 2_000_000_000);
+        assert!(list.subscription[0].suppress_redundant);
+        assert_eq!(list.subscription[0].heartbeat_interval, 60_000_000_000);
+        assert_eq!(list.subscription[1].mode(), SubscriptionMode::OnChange);
+        assert_eq!(list.subscription[1].path.as_ref().map(|p| p.origin.as_str()), Some("openconfig"));
+    }
+
+    #[tokio::test]
+    async fn test_dial_out(){
+        let pki = Pki::new("dial-out");
+        let (address, mut metrics_rx) = start_server(&pki).await;
+        let mut client = dial(&address, &pki.tls("mx1")).await;
+        let (tx, rx) = mpsc::channel(4);
+        let mut requests = client.dial_out_subscriber(ReceiverStream::new(rx)).await.unwrap().into_inner();
+        // the configured paths are subscribed once the device connected
+        let request = requests.next().await.unwrap().unwrap();
+        let Some(subscribe_request::Request::Subscribe(list)) = request.request.and_then(|r| r.request) else {
+            panic!("not a subscription");
+        };
+        assert_eq!(list.subscription[0].path, Some(parse_path("/interfaces/")));
+        // an empty target or the one of the certificate is accepted
+        tx.send(dial_out_response("")).await.unwrap();
+        tx.send(dial_out_response("mx1")).await.unwrap();
+        for _ in 0..2{
+            let metrics = tokio::time::timeout(Duration::from_secs(5), metrics_rx.recv()).await.unwrap().unwrap();
+            assert_eq!(metrics.labels.get("system_id"), Some(&"mx1".to_string()));
+            assert_eq!(metrics.labels.get("interface_name"), Some(&"et-0/0/0".to_string()));
+        }
+        // data of another device ends the stream
+        tx.send(dial_out_response("mx3")).await.unwrap();
+        let status = requests.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(metrics_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dial_out_unknown_device(){
+        let pki = Pki::new("unknown-device");
+        let (address, _metrics_rx) = start_server(&pki).await;
+        let mut client = dial(&address, &pki.tls("mx2")).await;
+        let (_tx, rx) = mpsc::channel::<DialOutResponse>(1);
+        let status = client.dial_out_subscriber(ReceiverStream::new(rx)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+}
@@ -0,0 +1 @@
+pub mod dialout;
\ No newline at end of file
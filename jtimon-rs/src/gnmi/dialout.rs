@@ -1,34 +1,28 @@
 // This file is @generated by prost-build.
-#[derive(serde::Deserialize, serde::Serialize)]
+/// sent by the collector
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CollectorMetrics {
-    #[prost(string, optional, tag = "1")]
-    pub namespace: ::core::option::Option<::prost::alloc::string::String>,
-    #[prost(map = "string, string", tag = "2")]
-    pub labels: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    #[prost(map = "string, uint64", tag = "3")]
-    pub metrics: ::std::collections::HashMap<::prost::alloc::string::String, u64>,
+pub struct DialOutRequest {
+    #[prost(message, optional, tag = "1")]
+    pub request: ::core::option::Option<super::gnmi::SubscribeRequest>,
 }
+/// sent by the device
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Reply {
-    #[prost(string, tag = "1")]
-    pub message: ::prost::alloc::string::String,
+pub struct DialOutResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub response: ::prost::alloc::vec::Vec<super::gnmi::SubscribeResponse>,
 }
 /// Generated client implementations.
-pub mod collector_server_client {
+pub mod subscriber_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
     #[derive(Debug, Clone)]
-    pub struct CollectorServerClient<T> {
+    pub struct SubscriberClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl CollectorServerClient<tonic::transport::Channel> {
+    impl SubscriberClient<tonic::transport::Channel> {
         /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
@@ -39,7 +33,7 @@ pub mod collector_server_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> CollectorServerClient<T>
+    impl<T> SubscriberClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
         T::Error: Into<StdError>,
@@ -57,7 +51,7 @@ pub mod collector_server_client {
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> CollectorServerClient<InterceptedService<T, F>>
+        ) -> SubscriberClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
             T::ResponseBody: Default,
@@ -71,7 +65,7 @@ pub mod collector_server_client {
                 http::Request<tonic::body::BoxBody>,
             >>::Error: Into<StdError> + Send + Sync,
         {
-            CollectorServerClient::new(InterceptedService::new(inner, interceptor))
+            SubscriberClient::new(InterceptedService::new(inner, interceptor))
         }
         /// Compress requests with the given encoding.
         ///
@@ -104,10 +98,13 @@ pub mod collector_server_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
-        pub async fn send_metrics(
+        pub async fn dial_out_subscriber(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<Message = super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status> {
+            request: impl tonic::IntoStreamingRequest<Message = super::DialOutResponse>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::DialOutRequest>>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -119,55 +116,38 @@ pub mod collector_server_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/collector.CollectorServer/SendMetrics",
+                "/dialout.Subscriber/DialOutSubscriber",
             );
             let mut req = request.into_streaming_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("collector.CollectorServer", "SendMetrics"));
-            self.inner.client_streaming(req, path, codec).await
-        }
-        pub async fn register_metrics(
-            &mut self,
-            request: impl tonic::IntoRequest<super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/collector.CollectorServer/RegisterMetrics",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("collector.CollectorServer", "RegisterMetrics"));
-            self.inner.unary(req, path, codec).await
+                .insert(GrpcMethod::new("dialout.Subscriber", "DialOutSubscriber"));
+            self.inner.streaming(req, path, codec).await
         }
     }
 }
 /// Generated server implementations.
-pub mod collector_server_server {
+pub mod subscriber_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with CollectorServerServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with SubscriberServer.
     #[async_trait]
-    pub trait CollectorServer: Send + Sync + 'static {
-        async fn send_metrics(
+    pub trait Subscriber: Send + Sync + 'static {
+        /// Server streaming response type for the DialOutSubscriber method.
+        type DialOutSubscriberStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::DialOutRequest, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn dial_out_subscriber(
             &self,
-            request: tonic::Request<tonic::Streaming<super::CollectorMetrics>>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status>;
-        async fn register_metrics(
-            &self,
-            request: tonic::Request<super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status>;
+            request: tonic::Request<tonic::Streaming<super::DialOutResponse>>,
+        ) -> std::result::Result<
+            tonic::Response<Self::DialOutSubscriberStream>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
-    pub struct CollectorServerServer<T: CollectorServer> {
+    pub struct SubscriberServer<T: Subscriber> {
         inner: _Inner<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
@@ -175,7 +155,7 @@ pub mod collector_server_server {
         max_encoding_message_size: Option<usize>,
     }
     struct _Inner<T>(Arc<T>);
-    impl<T: CollectorServer> CollectorServerServer<T> {
+    impl<T: Subscriber> SubscriberServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -227,9 +207,9 @@ pub mod collector_server_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for CollectorServerServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for SubscriberServer<T>
     where
-        T: CollectorServer,
+        T: Subscriber,
         B: Body + Send + 'static,
         B::Error: Into<StdError> + Send + 'static,
     {
@@ -245,73 +225,28 @@ pub mod collector_server_server {
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             let inner = self.inner.clone();
             match req.uri().path() {
-                "/collector.CollectorServer/SendMetrics" => {
+                "/dialout.Subscriber/DialOutSubscriber" => {
                     #[allow(non_camel_case_types)]
-                    struct SendMetricsSvc<T: CollectorServer>(pub Arc<T>);
+                    struct DialOutSubscriberSvc<T: Subscriber>(pub Arc<T>);
                     impl<
-                        T: CollectorServer,
-                    > tonic::server::ClientStreamingService<super::CollectorMetrics>
-                    for SendMetricsSvc<T> {
-                        type Response = super::Reply;
+                        T: Subscriber,
+                    > tonic::server::StreamingService<super::DialOutResponse>
+                    for DialOutSubscriberSvc<T> {
+                        type Response = super::DialOutRequest;
+                        type ResponseStream = T::DialOutSubscriberStream;
                         type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
+                            tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
                             request: tonic::Request<
-                                tonic::Streaming<super::CollectorMetrics>,
+                                tonic::Streaming<super::DialOutResponse>,
                             >,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CollectorServer>::send_metrics(&inner, request).await
-                            };
-                            Box::pin(fut)
-                        }
-                    }
-                    let accept_compression_encodings = self.accept_compression_encodings;
-                    let send_compression_encodings = self.send_compression_encodings;
-                    let max_decoding_message_size = self.max_decoding_message_size;
-                    let max_encoding_message_size = self.max_encoding_message_size;
-                    let inner = self.inner.clone();
-                    let fut = async move {
-                        let inner = inner.0;
-                        let method = SendMetricsSvc(inner);
-                        let codec = tonic::codec::ProstCodec::default();
-                        let mut grpc = tonic::server::Grpc::new(codec)
-                            .apply_compression_config(
-                                accept_compression_encodings,
-                                send_compression_encodings,
-                            )
-                            .apply_max_message_size_config(
-                                max_decoding_message_size,
-                                max_encoding_message_size,
-                            );
-                        let res = grpc.client_streaming(method, req).await;
-                        Ok(res)
-                    };
-                    Box::pin(fut)
-                }
-                "/collector.CollectorServer/RegisterMetrics" => {
-                    #[allow(non_camel_case_types)]
-                    struct RegisterMetricsSvc<T: CollectorServer>(pub Arc<T>);
-                    impl<
-                        T: CollectorServer,
-                    > tonic::server::UnaryService<super::CollectorMetrics>
-                    for RegisterMetricsSvc<T> {
-                        type Response = super::Reply;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
-                        fn call(
-                            &mut self,
-                            request: tonic::Request<super::CollectorMetrics>,
-                        ) -> Self::Future {
-                            let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as CollectorServer>::register_metrics(&inner, request)
+                                <T as Subscriber>::dial_out_subscriber(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -324,7 +259,7 @@ pub mod collector_server_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = RegisterMetricsSvc(inner);
+                        let method = DialOutSubscriberSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -335,7 +270,7 @@ pub mod collector_server_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.unary(method, req).await;
+                        let res = grpc.streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
@@ -355,7 +290,7 @@ pub mod collector_server_server {
             }
         }
     }
-    impl<T: CollectorServer> Clone for CollectorServerServer<T> {
+    impl<T: Subscriber> Clone for SubscriberServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -367,7 +302,7 @@ pub mod collector_server_server {
             }
         }
     }
-    impl<T: CollectorServer> Clone for _Inner<T> {
+    impl<T: Subscriber> Clone for _Inner<T> {
         fn clone(&self) -> Self {
             Self(Arc::clone(&self.0))
         }
@@ -377,7 +312,7 @@ pub mod collector_server_server {
             write!(f, "{:?}", self.0)
         }
     }
-    impl<T: CollectorServer> tonic::server::NamedService for CollectorServerServer<T> {
-        const NAME: &'static str = "collector.CollectorServer";
+    impl<T: Subscriber> tonic::server::NamedService for SubscriberServer<T> {
+        const NAME: &'static str = "dialout.Subscriber";
     }
 }
@@ -1,4 +1,7 @@
 // This file is @generated by prost-build.
+pub mod dialout {
+    include!("dialout.rs");
+}
 pub mod gnmi {
     include!("gnmi.rs");
 }
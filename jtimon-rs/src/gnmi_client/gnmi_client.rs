@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tonic::Request;
+use collector_protocol::collector::collector::CollectorMetrics;
+use crate::connection::connection::connect;
+use crate::mapping::mapping::{sanitize, split_elements};
+use crate::gnmi::gnmi::{
+    g_nmi_client::GNmiClient, typed_value::Value, CapabilityRequest, CapabilityResponse, Encoding, GetRequest,
+    Notification, Path, PathElem,
+};
+use crate::Tls;
+
+// One-shot gNMI operations. Junos authenticates gNMI calls with the
+// username and password passed as metadata of every request.
+pub struct GnmiClient{
+    client: GNmiClient<Channel>,
+    username: String,
+    password: String,
+}
+
+impl GnmiClient{
+    pub async fn connect(address: &str, tls: Option<&Tls>, username: String, password: String) -> anyhow::Result<GnmiClient>{
+        let channel = connect(address, tls).await?;
+        Ok(GnmiClient{
+            client: GNmiClient::new(channel),
+            username,
+            password,
+        })
+    }
+
+    pub async fn capabilities(&mut self) -> anyhow::Result<CapabilityResponse>{
+        let req = self.request(CapabilityRequest::default())?;
+        Ok(self.client.capabilities(req).await?.into_inner())
+    }
+
+    pub async fn get(&mut self, paths: &[String], encoding: Encoding) -> anyhow::Result<Vec<Notification>>{
+        let mut get_req = GetRequest{
+            path: paths.iter().map(|p| parse_path(p)).collect(),
+            ..Default::default()
+        };
+        get_req.set_encoding(encoding);
+        let req = self.request(get_req)?;
+        Ok(self.client.get(req).await?.into_inner().notification)
+    }
+
+    fn request<T>(&self, message: T) -> anyhow::Result<Request<T>>{
+        let mut req = Request::new(message);
+        req.metadata_mut().insert("username", MetadataValue::try_from(&self.username)?);
+        req.metadata_mut().insert("password", MetadataValue::try_from(&self.password)?);
+        Ok(req)
+    }
+}
+
+// parses /interfaces/interface[name=et-0/0/0]/state into path elements,
+// an origin can be given as <origin>:/path
+pub fn parse_path(path: &str) -> Path{
+    let (origin, path) = match path.split_once(":/"){
+        Some((origin, rest)) if !origin.contains('/') && !origin.contains('[') => (origin.to_string(), rest),
+        _ => (String::new(), path),
+    };
+    let mut elem = Vec::new();
+    for element in split_elements(path){
+        let (name, keys) = match element.find('['){
+            Some(start) => (&element[..start], &element[start..]),
+            None => (element, ""),
+        };
+        let mut key = HashMap::new();
+        for predicate in keys.split(['[', ']']).filter(|p| !p.is_empty()){
+            if let Some((k, v)) = predicate.split_once('='){
+                key.insert(k.trim().to_string(), v.trim().trim_matches(['\'', '"']).to_string());
+            }
+        }
+        elem.push(PathElem{
+            name: name.to_string(),
+            key,
+        });
+    }
+    Path{
+        origin,
+        elem,
+        ..Default::default()
+    }
+}
+
+// formats path elements as /interfaces/interface[name=et-0/0/0]/state
+pub fn format_path(path: &Path) -> String{
+    let mut formatted = String::new();
+    for elem in &path.elem{
+        formatted.push('/');
+        formatted.push_str(&elem.name);
+        let mut keys: Vec<(&String, &String)> = elem.key.iter().collect();
+        keys.sort();
+        for (k, v) in keys{
+            formatted.push_str(&format!("[{}={}]", k, v));
+        }
+    }
+    if formatted.is_empty(){
+        formatted.push('/');
+    }
+    formatted
+}
+
+// Converts the updates of a notification into metrics. The metric name is
+// built from the path element names and JSON member names, path keys become
+// <element>_<key> labels. In JSON values, numbers, numeric strings (JSON_IETF
+// encodes 64 bit integers as strings) and booleans are metrics, other strings
+// become labels of the numbers of their object.
+pub fn notification_metrics(notification: &Notification, base_labels: &HashMap<String, String>) -> Vec<CollectorMetrics>{
+    let mut metrics_list = Vec::new();
+    for update in &notification.update{
+        let mut elem: Vec<&PathElem> = notification.prefix.iter().flat_map(|p| p.elem.iter()).collect();
+        elem.extend(update.path.iter().flat_map(|p| p.elem.iter()));
+        let mut labels = base_labels.clone();
+        for e in &elem{
+            for (k, v) in &e.key{
+                labels.insert(sanitize(&format!("{}_{}", e.name, k)), v.clone());
+            }
+        }
+        let name = elem.iter().map(|e| sanitize(&e.name)).collect::<Vec<String>>().join("_");
+        let Some(value) = update.val.as_ref().and_then(|v| v.value.as_ref()) else {
+            continue;
+        };
+        let json = match value{
+            Value::JsonVal(v) | Value::JsonIetfVal(v) => serde_json::from_slice(v).ok(),
+            Value::StringVal(v) | Value::AsciiVal(v) => Some(serde_json::Value::String(v.clone())),
+            Value::IntVal(v) => Some(serde_json::Value::from(*v)),
+            Value::UintVal(v) => Some(serde_json::Value::from(*v)),
+            Value::BoolVal(v) => Some(serde_json::Value::from(*v)),
+            Value::FloatVal(v) => Some(serde_json::Value::from(*v)),
+            Value::DoubleVal(v) => Some(serde_json::Value::from(*v)),
+            _ => None,
+        };
+        if let Some(json) = json{
+            json_metrics(&json, &name, &labels, &mut metrics_list);
+        }
+    }
+    metrics_list
+}
+
+fn json_metrics(json: &serde_json::Value, name: &str, labels: &HashMap<String, String>, metrics_list: &mut Vec<CollectorMetrics>){
+    match json{
+        serde_json::Value::Object(members) => {
+            let mut labels = labels.clone();
+            let mut metrics = HashMap::new();
+            for (k, v) in members{
+                let member_name = join_name(name, k);
+                match v{
+                    serde_json::Value::String(s) if number(v).is_none() => {
+                        labels.insert(member_name_label(name, k), s.clone());
+                    },
+                    serde_json::Value::Object(_) | serde_json::Value::Array(_) => {},
+                    _ => {
+                        if let Some(value) = number(v){
+                            metrics.insert(member_name, value);
+                        }
+                    },
+                }
+            }
+            if !metrics.is_empty(){
+                metrics_list.push(CollectorMetrics{
+                    namespace: None,
+                    labels: labels.clone(),
+                    metrics,
+                });
+            }
+            for (k, v) in members{
+                if v.is_object() || v.is_array(){
+                    json_metrics(v, &join_name(name, k), &labels, metrics_list);
+                }
+            }
+        },
+        serde_json::Value::Array(elements) => {
+            for element in elements{
+                json_metrics(element, name, labels, metrics_list);
+            }
+        },
+        _ => {
+            if let Some(value) = number(json){
+                metrics_list.push(CollectorMetrics{
+                    namespace: None,
+                    labels: labels.clone(),
+                    metrics: HashMap::from([(name.to_string(), value)]),
+                });
+            }
+        },
+    }
+}
+
+// metric values are unsigned, negative numbers are not metrics
+fn number(json: &serde_json::Value) -> Option<u64>{
+    match json{
+        serde_json::Value::Number(n) => n.as_u64().or(n.as_f64().filter(|v| *v >= 0.0).map(|v| v as u64)),
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Bool(b) => Some(*b as u64),
+        _ => None,
+    }
+}
+
+// JSON_IETF member names can be qualified with the module, e.g.
+// openconfig-interfaces:state
+fn join_name(name: &str, member: &str) -> String{
+    let member = sanitize(member.rsplit(':').next().unwrap_or(member));
+    if name.is_empty(){
+        member
+    } else {
+        format!("{}_{}", name, member)
+    }
+}
+
+fn member_name_label(name: &str, member: &str) -> String{
+    let parent = name.rsplit('_').next().unwrap_or_default();
+    join_name(parent, member)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::gnmi::gnmi::{TypedValue, Update};
+
+    #[test]
+    fn test_parse_path(){
+        let path = parse_path("openconfig:/interfaces/interface[name=et-0/0/0]/subinterfaces/subinterface[index='0']/state");
+        assert_eq!(path.origin, "openconfig");
+        assert_eq!(path.elem.len(), 5);
+        assert_eq!(path.elem[1].key.get("name"), Some(&"et-0/0/0".to_string()));
+        assert_eq!(path.elem[3].key.get("index"), Some(&"0".to_string()));
+        assert_eq!(format_path(&path), "/interfaces/interface[name=et-0/0/0]/subinterfaces/subinterface[index=0]/state");
+    }
+
+    #[test]
+    fn test_json_ietf_metrics(){
+        let json = r#"{"openconfig-interfaces:interface": [{"name": "et-0/0/0", "state": {"oper-status": "UP", "mtu": 9192, "counters": {"in-octets": "1000"}}}]}"#;
+        let notification = Notification{
+            update: vec![Update{
+                path: Some(parse_path("/interfaces")),
+                val: Some(TypedValue{
+                    value: Some(Value::JsonIetfVal(json.as_bytes().to_vec())),
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let metrics = notification_metrics(&notification, &HashMap::new());
+        let state = metrics.iter().find(|m| m.metrics.contains_key("interfaces_interface_state_mtu")).unwrap();
+        assert_eq!(state.labels.get("interface_name"), Some(&"et-0/0/0".to_string()));
+        assert_eq!(state.labels.get("state_oper_status"), Some(&"UP".to_string()));
+        let counters = metrics.iter().find(|m| m.metrics.contains_key("interfaces_interface_state_counters_in_octets")).unwrap();
+        assert_eq!(counters.metrics.get("interfaces_interface_state_counters_in_octets"), Some(&1000));
+        assert_eq!(counters.labels.get("interface_name"), Some(&"et-0/0/0".to_string()));
+    }
+}
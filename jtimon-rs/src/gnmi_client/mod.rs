@@ -0,0 +1 @@
+pub mod gnmi_client;
\ No newline at end of file
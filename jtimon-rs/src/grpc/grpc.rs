@@ -1,51 +1,59 @@
 use std::collections::HashMap;
-use std::hash::Hash;
-use crate::collector::collector::CollectorMetrics;
-use crate::collector_client::collector_client::Client as CollClient;
-use crate::telemetry::telemetry::key_value::Value;
+use anyhow::Context;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::watch;
+use collector_protocol::sender::sender::Client as CollClient;
 use crate::telemetry::telemetry::{
-    Path, SubscriptionAdditionalConfig, SubscriptionMode, SubscriptionRequest
+    CancelSubscriptionRequest, GetOperationalStateRequest, GetSubscriptionsRequest, Path, ReturnCode,
+    SubscriptionAdditionalConfig, SubscriptionMode, SubscriptionRequest
 };
 use futures::StreamExt;
 use tonic::Request as GrpcRequest;
 use crate::Path as ConfigPath;
+use crate::Subscription;
 use crate::jnx::jnx::jet::authentication as junos_auth;
+use crate::jnx::jnx::jet::common::StatusCode;
+use crate::jnx::jnx::jet::management::{
+    management_client::ManagementClient, op_command_get_request, OpCommandGetRequest, OpCommandOutputFormat,
+};
+use crate::opcommand::opcommand::{extract_metrics, parse_reply, Format, OpCommand};
 use crate::Tls;
+use crate::connection::connection::connect;
+use super::opstate::opstate_metrics;
+use crate::converter::assembler::Assembler;
+use crate::converter::converter::Converter;
+use crate::recorder::recorder::Client as RecClient;
+use crate::recording::recording::Source;
+use tonic::transport::Channel;
 use crate::telemetry::telemetry::open_config_telemetry_client::OpenConfigTelemetryClient;
 use log::error;
-use tonic::metadata::MetadataMap;
-use tonic::transport::{Certificate, Channel, ClientTlsConfig};
 use log::info;
+use log::trace;
+use log::warn;
 pub struct Grpc{
     client: Client,
 }
 
 impl Grpc{
-    pub async fn new(address: String, tls: Tls, username: String, password: String, collector_client: CollClient) -> anyhow::Result<Self>{
-        let mut map = MetadataMap::new();
-        let ca = std::fs::read(tls.ca_file).unwrap();
-        let crt = std::fs::read(tls.cert_file).unwrap();
-        let key = std::fs::read(tls.key_file).unwrap();
-        map.insert("client-id", "cnm".parse().unwrap());
-        let identity = tonic::transport::Identity::from_pem(crt, key);
-        let tls = ClientTlsConfig::new()
-            .domain_name(tls.server_name)
-            .identity(identity)
-            .ca_certificate(Certificate::from_pem(ca));
+    pub async fn new(address: String, tls: Option<Tls>, username: String, password: String, client_id: String, collector_client: CollClient, recorder: RecClient) -> anyhow::Result<Self>{
+        let channel = Self::login(&address, tls.as_ref(), username, password, client_id).await?;
+        let client = OpenConfigTelemetryClient::new(channel.clone());
+        let management_client = ManagementClient::new(channel);
+        let client = Client{junos_client: client, management_client, collector_client, recorder, address, subscription_ids: Arc::default()};
+        Ok(Self{client})
+    }
 
-        let ep_address = format!("https://{}",address);
-        info!("Connecting to {}", ep_address);
-        let channel = Channel::from_shared(ep_address.clone())?
-            .tls_config(tls)?
-            .connect()
-            .await?;
-        info!("Connected to {}", ep_address);
+    // connects to the device and logs in, the returned channel can be used
+    // for any of the Junos services
+    pub async fn login(address: &str, tls: Option<&Tls>, username: String, password: String, client_id: String) -> anyhow::Result<Channel>{
+        let channel = connect(address, tls).await?;
+        info!("Connected to {}", address);
 
         let login_request = junos_auth::LoginRequest{
             username,
             password,
-            group_id: "cnm".to_string(),
-            client_id: "cnm".to_string(),
+            group_id: client_id.clone(),
+            client_id,
         };
 
         let login_response = match junos_auth::authentication_client::AuthenticationClient::new(channel.clone()).login(login_request).await{
@@ -57,10 +65,12 @@ impl Grpc{
                 return Err(e.into())
             }
         };
-        info!("login response: {:#?}", login_response.into_inner());
-        let client = OpenConfigTelemetryClient::new(channel);
-        let client = Client{junos_client: client, collector_client};
-        Ok(Self{client})
+        match login_response.into_inner().status{
+            Some(status) if status.code() == StatusCode::Success => info!("Logged in to {}", address),
+            Some(status) => anyhow::bail!("Login to {} failed: {}", address, status.message),
+            None => info!("Logged in to {}, no status returned", address),
+        }
+        Ok(channel)
     }
     pub fn client(&self) -> Client{
         self.client.clone()
@@ -70,283 +80,207 @@ impl Grpc{
 #[derive(Clone)]
 pub struct Client{
     junos_client: OpenConfigTelemetryClient<tonic::transport::Channel>,
+    management_client: ManagementClient<tonic::transport::Channel>,
     collector_client: CollClient,
+    recorder: RecClient,
+    address: String,
+    // the device ids of the open subscriptions by subscription name
+    subscription_ids: Arc<Mutex<HashMap<String, u32>>>,
 }
 
-#[derive(Hash, Eq, PartialEq, Clone, Debug)]
-pub struct MetricsKey{
-    namespace: String,
-    system_id: String,
-    counter_name: String,
-}
-
-#[derive(Clone, Debug)]
-struct OpenConfigMetricsData{
-    data: HashMap<String, u64>,
-    metrics_labels: HashMap<String, String>,
-}
-
-impl OpenConfigMetricsData{
-    pub fn new() -> Self{
-        Self{
-            data: HashMap::new(),
-            metrics_labels: HashMap::new(),
-        }
-    }
-    pub fn add(&mut self, key: String, value: u64, rate: u64){
-        self.data.insert(key.clone(), value);
-        self.data.insert(format!("{}_per_sec", key), rate);
-    }
-    pub fn add_labels(&mut self, labels: HashMap<String, String>){
-        for (k,v) in labels{
-            self.metrics_labels.insert(k, v);
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct OpenConfigMetrics{
-    prefix: String,
-    metrics_data: HashMap<String,OpenConfigMetricsData>,
-    prefix_labels: HashMap<String, String>,
-    ts: u64,
-    system_id: String,
-    namespace: String,
-}
-
-impl OpenConfigMetrics{
-    pub fn new(prefix: String, system_id: String, namespace: String) -> Self{
-        Self{
-            prefix,
-            metrics_data: HashMap::new(),
-            prefix_labels: HashMap::new(),
-            ts: 0,
-            system_id,
-            namespace,
-        }
-    }
-    pub fn add_metrics_data(&mut self, counter_key: String, counter_name: String, value: u64, labels: HashMap<String, String>, rate: u64){
-        if let Some(data) = self.metrics_data.get_mut(&counter_key){
-            data.add(counter_name, value, rate);
-            data.add_labels(labels);
+impl Client{
+    // cancels the subscription opened by subscribe_and_receive with this
+    // name, subscriptions of other clients are left alone
+    pub async fn cancel_subscription(&mut self, name: &str) -> anyhow::Result<()>{
+        let Some(subscription_id) = self.subscription_ids.lock().unwrap().remove(name) else {
+            return Ok(());
+        };
+        let req = CancelSubscriptionRequest{
+            subscription_id,
+        };
+        let reply = self.junos_client.cancel_telemetry_subscription(req).await?.into_inner();
+        if reply.code() == ReturnCode::Success{
+            info!("Cancelled subscription {}", subscription_id);
         } else {
-            let mut data = OpenConfigMetricsData::new();
-            data.add(counter_name, value, rate);
-            data.add_labels(labels);
-            self.metrics_data.insert(counter_key, data);
+            error!("Failed to cancel subscription {}: {}", subscription_id, reply.code_str);
         }
+        Ok(())
     }
 
-    pub fn get_metrics_data(&self, counter_name: &String) -> Option<&OpenConfigMetricsData>{
-        self.metrics_data.get(counter_name)
-    }
-
-    pub fn key(&self) -> String{
-        // key consists of system_id and the concatenation of all prefix_label values
-        let mut key = self.system_id.clone();
-        for (_,v) in &self.prefix_labels{
-            key.push_str("_");
-            key.push_str(&v);
+    // ids of the subscriptions on the device whose path list matches paths
+    async fn matching_subscriptions(&mut self, paths: &[ConfigPath]) -> anyhow::Result<Vec<u32>>{
+        let mut wanted: Vec<&str> = paths.iter().map(|p| p.path.as_str()).collect();
+        wanted.sort();
+        let req = GetSubscriptionsRequest{
+            subscription_id: u32::MAX,
+        };
+        let res = self.junos_client.get_telemetry_subscriptions(req).await?;
+        let mut ids = Vec::new();
+        for subscription in res.into_inner().subscription_list{
+            let mut sub_paths: Vec<&str> = subscription.path_list.iter().map(|p| p.path.as_str()).collect();
+            sub_paths.sort();
+            if sub_paths != wanted{
+                continue;
+            }
+            if let Some(response) = subscription.response{
+                ids.push(response.subscription_id);
+            }
         }
-        key
+        Ok(ids)
     }
-}
 
-#[derive(Debug)]
-struct OpenConfigMetricsList(Vec<OpenConfigMetrics>);
-
-impl OpenConfigMetricsList{
-    pub fn new() -> Self{
-        Self(Vec::new())
-    }
-    pub fn add(&mut self, prefix: String, prefix_labels: HashMap<String,String>, ts: u64, system_id: String, namespace: String){
-        let mut metrics = OpenConfigMetrics::new(prefix, system_id, namespace);
-        metrics.prefix_labels = prefix_labels;
-        metrics.ts = ts;
-        self.0.push(metrics);
-    }
-    pub fn add_counter(&mut self, counter_key: String, counter_name: String, counter_labels: HashMap<String, String>, value: u64, rate: u64){
-        if let Some(last_open_config_metrix) = self.0.last_mut(){
-            let full_counter_name = format!("{}_{}", last_open_config_metrix.prefix, counter_name);
-            last_open_config_metrix.add_metrics_data(counter_key, full_counter_name, value, counter_labels, rate);
+    // polls the operational state of the telemetry agent on the device and
+    // sends it as metrics
+    pub async fn poll_operational_state(&mut self, interval: u64, namespace: String, system_id: watch::Receiver<Option<String>>) -> anyhow::Result<()>{
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(interval));
+        let mut labels = HashMap::new();
+        labels.insert("namespace".to_string(), namespace);
+        labels.insert("system_id".to_string(), wait_for_system_id(system_id).await?);
+        loop{
+            interval.tick().await;
+            let req = GetOperationalStateRequest{
+                subscription_id: u32::MAX,
+                ..Default::default()
+            };
+            let reply = match self.junos_client.get_telemetry_operational_state(req).await{
+                Ok(reply) => reply.into_inner(),
+                Err(e) => {
+                    error!("Failed to get operational state: {:?}", e);
+                    continue;
+                }
+            };
+            for collector_metrics in opstate_metrics(&reply.kv, &labels){
+                self.collector_client.send(collector_metrics).await?;
+            }
         }
     }
 
-    pub fn get_key(&self) -> String{
-        if let Some(last_open_config_metrix) = self.0.last(){
-            last_open_config_metrix.key()
-        } else {
-            "default".to_string()
+    // runs an operational command periodically and sends the values
+    // selected by its extractors as metrics
+    pub async fn poll_command(&mut self, command: OpCommand, namespace: String, system_id: watch::Receiver<Option<String>>) -> anyhow::Result<()>{
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(command.interval));
+        let mut labels = HashMap::new();
+        labels.insert("namespace".to_string(), namespace);
+        labels.insert("system_id".to_string(), wait_for_system_id(system_id).await?);
+        let out_format = match command.format{
+            Format::Json => OpCommandOutputFormat::OpCommandOutputJson,
+            Format::Xml => OpCommandOutputFormat::OpCommandOutputXml,
+        };
+        loop{
+            interval.tick().await;
+            let mut req = OpCommandGetRequest{
+                command: Some(op_command_get_request::Command::CliCommand(command.command.clone())),
+                ..Default::default()
+            };
+            req.set_out_format(out_format);
+            let data = match self.run_command(req).await{
+                Ok(data) => data,
+                Err(e) => {
+                    error!("Failed to run command {}: {:?}", command.command, e);
+                    continue;
+                }
+            };
+            let reply = match parse_reply(&data, command.format){
+                Ok(reply) => reply,
+                Err(e) => {
+                    error!("Failed to parse reply of command {}: {:?}", command.command, e);
+                    continue;
+                }
+            };
+            for collector_metrics in extract_metrics(&reply, &command.extractors, &labels){
+                self.collector_client.send(collector_metrics).await?;
+            }
         }
     }
 
-    pub fn ts(&self) -> u64{
-        self.0.last().unwrap().ts
+    async fn run_command(&mut self, req: OpCommandGetRequest) -> anyhow::Result<String>{
+        let mut s = self.management_client.op_command_get(req).await?.into_inner();
+        let mut data = String::new();
+        while let Some(res) = s.next().await{
+            let res = res?;
+            if let Some(status) = &res.status{
+                if status.code() != StatusCode::Success{
+                    return Err(anyhow::anyhow!("{}", status.message));
+                }
+            }
+            data.push_str(&res.data);
+        }
+        Ok(data)
     }
-}   
 
-impl Client{
-    pub async fn subscribe_and_receive(&mut self, paths: Vec<ConfigPath>, username: String, password: String, namespace: String) -> anyhow::Result<()>{
+    // receives the data of a subscription and sends it as metrics, the first
+    // system id of the data is published to system_id
+    pub async fn subscribe_and_receive(&mut self, client_id: String, subscription: Subscription, username: String, password: String, namespace: String, system_id: &watch::Sender<Option<String>>) -> anyhow::Result<()>{
+        let paths = subscription.paths;
         let mut sub_req = SubscriptionRequest::default();
         let mut add_config = SubscriptionAdditionalConfig::default();
         add_config.set_mode(SubscriptionMode::LongLived);
         add_config.need_eos = true;
+        add_config.limit_records = subscription.limit_records.unwrap_or_default();
+        add_config.limit_time_seconds = subscription.limit_time_seconds.unwrap_or_default();
         sub_req.additional_config = Some(add_config);
         
         let mut path_list: Vec<Path> = Vec::new();
         for p in &paths{
-            let mut path = Path::default();
-            path.path = p.path.clone();
-            path.sample_frequency = p.freq;
+            let path = Path{
+                path: p.path.clone(),
+                filter: p.filter.clone().unwrap_or_default(),
+                suppress_unchanged: p.suppress_unchanged,
+                max_silent_interval: p.max_silent_interval,
+                sample_frequency: p.freq,
+                need_eom: p.need_eom,
+                origin: p.origin.clone().unwrap_or_default(),
+                ..Default::default()
+            };
             path_list.push(path);
         }
         sub_req.path_list = path_list;
         let mut req = GrpcRequest::new(sub_req);
-        req.metadata_mut().insert("client-id", "cnm".parse().unwrap());
-        req.metadata_mut().insert("username", username.parse().unwrap());
-        req.metadata_mut().insert("password", password.parse().unwrap()); 
+        // the credentials come from env variables and files, they may hold
+        // characters which can't be sent in a header
+        req.metadata_mut().insert("client-id", client_id.parse().context("client id is not a valid header value")?);
+        req.metadata_mut().insert("username", username.parse().context("username is not a valid header value")?);
+        req.metadata_mut().insert("password", password.parse().context("password is not a valid header value")?);
+        // the data of a subscription doesn't carry its id, it is the id of
+        // the subscription to the paths which didn't exist before. The
+        // subscriptions to a device are opened one at a time, so only this
+        // one can show up while the lock is held.
+        let lock = subscribe_lock(&self.address);
+        let guard = lock.lock().await;
+        let existing = self.matching_subscriptions(&paths).await.unwrap_or_default();
         let res = self.junos_client.telemetry_subscribe(req).await?;
+        match self.matching_subscriptions(&paths).await{
+            Ok(ids) => match ids.into_iter().filter(|id| !existing.contains(id)).collect::<Vec<_>>()[..]{
+                [id] => {
+                    info!("Subscription {} has id {}", subscription.name, id);
+                    self.subscription_ids.lock().unwrap().insert(subscription.name.clone(), id);
+                },
+                _ => warn!("Could not identify subscription {}, it isn't cancelled when stopped", subscription.name),
+            },
+            Err(e) => warn!("Failed to get the id of subscription {}: {:?}", subscription.name, e),
+        }
+        drop(guard);
         let mut s = res.into_inner();
-        let label_re = regex::Regex::new(r"\[(.*?=.*?)\]").unwrap();
-        let prefix_re = regex::Regex::new(r"\[(.*?)\]").unwrap();
-        let mut prev_metrics_map: HashMap<String, OpenConfigMetrics> = HashMap::new();
+        let mut assembler = Assembler::new(&paths);
+        let mut converter = Converter::new(&paths, namespace)?;
         while let Some(res) = s.next().await {
             match res{
-                Ok(mut x) => {
+                Ok(x) => {
                     //info!("Received: {:#?}", x);
-                    let data_path: Vec<&str> = x.path.split(":").collect();
-                    if data_path.len() > 1 {
-                        let p = data_path[1];
-                        for path in &paths{
-                            if path.path == p{
-                                let mut open_config_metrics_list = OpenConfigMetricsList::new();
-                                let mut ts = 0;
-                                for kv in &mut x.kv{
-                                    if kv.key == "__timestamp__"{
-                                        ts = convert_value(&kv.value.as_ref().unwrap());
-                                    } else if kv.key == "index" {
-                                        continue;
-                                    } else if kv.key == "__prefix__"{  
-                                        if let Some(value) = &kv.value{
-                                            match value{
-                                                Value::StrValue(v) => {
-                                                    let mut prefix_labels = HashMap::new();
-                                                    for captures in label_re.captures_iter(v){
-                                                        for (idx, capture) in captures.iter().enumerate(){
-                                                            if idx == 0 {
-                                                                continue;
-                                                            }
-                                                            if let Some(capture) = capture{
-                                                                let capture = capture.as_str();
-                                                                let key_value = capture.split("=").collect::<Vec<&str>>();
-                                                                if key_value.len() == 2 {
-                                                                    let key = format!("prefix_{}", key_value[0]).replace("-", "_");
-                                                                    let value = key_value[1].to_string().replace("'", "").replace("-", "_");
-                                                                    if !value.is_empty(){
-                                                                        prefix_labels.insert(key, value);
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                    let prefix = prefix_re.replace_all(&v, "").to_string().replace("/", "__").replace("-", "_");
-                                                    open_config_metrics_list.add(prefix.clone(), prefix_labels, ts, x.system_id.clone(), namespace.clone());
-                                                },
-                                                _ => {}
-                                            }
-                                        }
-                                        //"/cos/interfaces/interface[name='et-0/0/8']/queues/queue[queue='8']/",
-                                    } else {
-                                        if let Some(value) = &kv.value{
-                                            let converted_value = convert_value(value);
-                                            let counter_name = prefix_re.replace_all(&kv.key, "").to_string().replace("/", "__");
-                                            let mut labels_map = HashMap::new();
-                                            let mut counter_key = "".to_string();
-                                            for captures in label_re.captures_iter(&kv.key){
-                                                for (idx, capture) in captures.iter().enumerate(){
-                                                    if idx == 0 {
-                                                        continue;
-                                                    }
-                                                    if let Some(capture) = capture{
-                                                        let capture = capture.as_str();
-                                                        let key_value = capture.split("=").collect::<Vec<&str>>();
-                                                        if key_value.len() == 2 {
-
-                                                            let key = key_value[0].replace("'", "").replace("-", "_");
-                                                            if key.is_empty(){
-                                                                continue;
-                                                            }
-                                                            
-                                                            let value = key_value[1].to_string().replace("'", "");
-                                                            if value.is_empty(){
-                                                                continue;
-                                                            }
-                                                            if idx > 1 {
-                                                                counter_key.push_str("_");
-                                                            }
-                                                            counter_key.push_str(&value);
-                                                            let key = format!("counter_{}", key);
-                                                            labels_map.insert(key, value);
-
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            if counter_key.is_empty(){
-                                                counter_key = open_config_metrics_list.get_key();
-                                            }
-                                            if !counter_key.is_empty(){
-                                                let open_config_metrics_key = open_config_metrics_list.get_key();
-                                                let mut rate = 0;
-                                                if let Some(prev_open_config_metrics) = prev_metrics_map.get(&open_config_metrics_key){
-                                                    if let Some(open_config_metrics_data) = prev_open_config_metrics.get_metrics_data(&counter_key){
-                                                        if let Some(prev_data) = open_config_metrics_data.data.get(&format!("{}_{}", prev_open_config_metrics.prefix, counter_name)){
-                                                            let prev_ts = prev_open_config_metrics.ts;
-                                                            let actual_ts = open_config_metrics_list.ts();
-                                                            let actual_data = converted_value;
-                                                            let data_delta = if actual_data >= *prev_data{
-                                                                actual_data - *prev_data
-                                                            } else {
-                                                                actual_data
-                                                            };
-                                                            if data_delta == 0{
-                                                                rate = 0;
-                                                            } else {
-                                                                let ts_delta = actual_ts - prev_ts;
-                                                                let per_sec_factor = ts_delta as f64 / 1_000.0;
-                                                                rate = (data_delta as f64 / per_sec_factor) as u64;
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                                //info!("Counter key: {}, counter name: {}, value: {}, rate: {}", counter_key, counter_name, converted_value, rate);
-                                                open_config_metrics_list.add_counter(counter_key, counter_name, labels_map, converted_value, rate);
-                                            }
-                                        }
-                                    }
-                                }
-                               
-                                info!("\n\n\n");
-                                for open_config_metrics in &open_config_metrics_list.0{
-                                    let key = open_config_metrics.key();
-                                    prev_metrics_map.insert(key.clone(), open_config_metrics.clone());
-
-                                    for (_open_config_metrics_key, open_config_metrics_data) in &open_config_metrics.metrics_data{
-                                        let mut labels_map = open_config_metrics.prefix_labels.clone();
-                                        let mut collector_metrics = CollectorMetrics::default();
-                                        for (key, value) in &open_config_metrics_data.data{
-                                            labels_map.extend(open_config_metrics_data.metrics_labels.clone());
-                                            collector_metrics.metrics.insert(key.clone(), *value);
-                                        }
-                                        labels_map.insert("namespace".to_string(), open_config_metrics.namespace.clone());
-                                        labels_map.insert("system_id".to_string(), open_config_metrics.system_id.clone());
-                                        collector_metrics.labels = labels_map;
-                                        info!("Sending metrics: {:#?}", collector_metrics);
-                                        self.collector_client.send(collector_metrics).await?;
-                                    } 
-                                }
-                            }
+                    self.recorder.open_config_data(Source::Device, &self.address, &x);
+                    system_id.send_if_modified(|id| {
+                        if id.is_some(){
+                            return false;
                         }
+                        *id = Some(x.system_id.clone());
+                        true
+                    });
+                    let Some(sample) = assembler.add(x) else {
+                        continue;
+                    };
+                    for collector_metrics in converter.convert(&sample){
+                        trace!("Sending metrics: {:?}", collector_metrics);
+                        self.collector_client.send(collector_metrics).await?;
                     }
                 },
                 Err(e) => {
@@ -354,27 +288,115 @@ impl Client{
                 }
             }
         };
+        self.subscription_ids.lock().unwrap().remove(&subscription.name);
         info!("Done");
         Ok(())
     }
 }
 
-fn convert_value(value: &Value) -> u64{
-    match value{
-        Value::DoubleValue(v) => {
-            *v as u64
-        },
-        Value::FloatValue(v) => {
-            *v as u64
-        },
-        Value::UintValue(v) => {
-            *v
-        },
-        Value::IntValue(v) => {
-            *v as u64
-        },
-        _ => {
-            0
+// Locks by device address held while a subscription is opened, shared by
+// the clients of all sessions since every subscription logs in on its own.
+static SUBSCRIBE_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = LazyLock::new(Mutex::default);
+
+fn subscribe_lock(address: &str) -> Arc<tokio::sync::Mutex<()>>{
+    SUBSCRIBE_LOCKS.lock().unwrap().entry(address.to_string()).or_default().clone()
+}
+
+// the system id of the device once it is known
+async fn wait_for_system_id(mut system_id: watch::Receiver<Option<String>>) -> anyhow::Result<String>{
+    let id = system_id.wait_for(|id| id.is_some()).await?;
+    Ok(id.clone().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use mock_junos::mock::mock::MockServer;
+    use std::time::Duration;
+    use mock_junos::script::script::Script;
+    use tokio::sync::mpsc;
+
+    const SCRIPT: &str = r#"
+user: USER
+password: PWD
+interval: 50
+repeat: true
+messages:
+- system_id: qfx1
+  path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+  kv:
+  - key: __prefix__
+    str: "/interfaces/interface[name='et-0/0/0']/"
+  - key: state/counters/in-octets
+    uint: 1000
+"#;
+
+    fn subscription(name: &str) -> Subscription{
+        Subscription{
+            name: name.to_string(),
+            paths: serde_yaml::from_str("- path: /interfaces/\n  freq: 1000").unwrap(),
+            limit_records: None,
+            limit_time_seconds: None,
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_login_failure(){
+        let script: Script = serde_yaml::from_str(SCRIPT).unwrap();
+        let address = MockServer::new(script).spawn().await.unwrap().to_string();
+        let res = Grpc::login(&address, None, "USER".to_string(), "WRONG".to_string(), "cid".to_string()).await;
+        assert!(res.is_err());
+        assert!(Grpc::login(&address, None, "USER".to_string(), "PWD".to_string(), "cid".to_string()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_header_value(){
+        let script: Script = serde_yaml::from_str(SCRIPT).unwrap();
+        let address = MockServer::new(script).spawn().await.unwrap().to_string();
+        let (tx, _rx) = mpsc::channel(100);
+        let grpc = Grpc::new(address, None, "USER".to_string(), "PWD".to_string(), "cid".to_string(), CollClient::new(tx, String::new()), RecClient::disabled()).await.unwrap();
+        let (system_id_tx, _) = watch::channel(None);
+        // a password file with an inner line break
+        let res = grpc.client().subscribe_and_receive("cid".to_string(), subscription("a"), "USER".to_string(), "PWD\nPWD".to_string(), "qfx".to_string(), &system_id_tx).await;
+        assert!(res.unwrap_err().to_string().contains("password"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_own_subscription(){
+        let script: Script = serde_yaml::from_str(SCRIPT).unwrap();
+        let address = MockServer::new(script).spawn().await.unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let collector_client = CollClient::new(tx, String::new());
+        let grpc = Grpc::new(address, None, "USER".to_string(), "PWD".to_string(), "cid".to_string(), collector_client, RecClient::disabled()).await.unwrap();
+        let mut client = grpc.client();
+        let paths = subscription("a").paths;
+
+        // both subscriptions have the same paths and are opened at once like
+        // the subscriptions of a session
+        let (system_id_tx, mut system_id_rx) = watch::channel(None);
+        let system_id_tx = Arc::new(system_id_tx);
+        for name in ["a", "b"]{
+            let mut sub_client = grpc.client();
+            let sub = subscription(name);
+            let system_id_tx = system_id_tx.clone();
+            tokio::spawn(async move {
+                sub_client.subscribe_and_receive(format!("cid-{}", sub.name), sub, "USER".to_string(), "PWD".to_string(), "qfx".to_string(), &system_id_tx).await
+            });
+        }
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.subscription_ids.lock().unwrap().len() < 2{
+                rx.recv().await.unwrap();
+            }
+        }).await.expect("both subscriptions got their id");
+        assert_eq!(system_id_rx.borrow_and_update().as_deref(), Some("qfx1"));
+        let ids = client.subscription_ids.lock().unwrap().clone();
+        assert_ne!(ids["a"], ids["b"]);
+        assert_eq!(client.matching_subscriptions(&paths).await.unwrap().len(), 2);
+
+        client.cancel_subscription("a").await.unwrap();
+        assert_eq!(client.matching_subscriptions(&paths).await.unwrap(), vec![ids["b"]]);
+        // cancelling again doesn't touch b
+        client.cancel_subscription("a").await.unwrap();
+        assert_eq!(client.matching_subscriptions(&paths).await.unwrap(), vec![ids["b"]]);
+    }
+}
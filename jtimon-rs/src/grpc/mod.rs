@@ -1 +1,2 @@
-pub mod grpc;
\ No newline at end of file
+pub mod grpc;
+pub mod opstate;
\ No newline at end of file
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use collector_protocol::collector::collector::CollectorMetrics;
+use collector_protocol::names::names::label_name;
+use log::warn;
+use crate::telemetry::telemetry::{key_value::Value, KeyValue};
+
+// The operational state reply is a flat list of key/values where string
+// values describe the entity (subscription, path, ...) the following numeric
+// values belong to. String values become labels, numeric values become
+// metrics prefixed with opstate_, a group is emitted whenever a string value
+// follows numeric values. Entities nest in the order their keys first show
+// up, e.g. the paths of a subscription follow its subscription_id: a string
+// value keeps the labels of the enclosing entities and clears those of the
+// entities nested in it. Every group carries the labels of all string keys
+// of the reply, those not set are empty, so all series of a metric have the
+// same labels.
+pub fn opstate_metrics(kv_list: &[KeyValue], base_labels: &HashMap<String, String>) -> Vec<CollectorMetrics>{
+    let mut labels = base_labels.clone();
+    // the string keys, outermost first
+    let mut nesting: Vec<String> = Vec::new();
+    for kv in kv_list{
+        if let Some(Value::StrValue(_)) = kv.value{
+            let key = sanitize(&kv.key);
+            if !nesting.contains(&key){
+                labels.entry(key.clone()).or_default();
+                nesting.push(key);
+            }
+        }
+    }
+    let mut metrics_list = Vec::new();
+    let mut metrics = HashMap::new();
+    for kv in kv_list{
+        let Some(value) = &kv.value else {
+            continue;
+        };
+        let key = sanitize(&kv.key);
+        let number = match value{
+            Value::StrValue(v) => {
+                if !metrics.is_empty(){
+                    metrics_list.push(CollectorMetrics{
+                        namespace: None,
+                        labels: labels.clone(),
+                        metrics: std::mem::take(&mut metrics),
+                    });
+                }
+                let depth = nesting.iter().position(|k| *k == key).unwrap_or_default();
+                for inner in &nesting[depth + 1..]{
+                    labels.insert(inner.clone(), String::new());
+                }
+                labels.insert(key, v.clone());
+                continue;
+            },
+            Value::DoubleValue(v) => if *v < 0.0 { negative(&key, v) } else { Some(*v as u64) },
+            Value::FloatValue(v) => if *v < 0.0 { negative(&key, v) } else { Some(*v as u64) },
+            Value::IntValue(v) | Value::SintValue(v) => u64::try_from(*v).ok().or_else(|| negative(&key, v)),
+            Value::UintValue(v) => Some(*v),
+            Value::BoolValue(v) => Some(*v as u64),
+            Value::BytesValue(_) | Value::LeaflistValue(_) => continue,
+        };
+        if let Some(number) = number{
+            metrics.insert(format!("opstate_{}", key), number);
+        }
+    }
+    if !metrics.is_empty(){
+        metrics_list.push(CollectorMetrics{
+            namespace: None,
+            labels,
+            metrics,
+        });
+    }
+    metrics_list
+}
+
+// metric values are unsigned, negative values are dropped
+fn negative(key: &str, value: impl std::fmt::Display) -> Option<u64>{
+    warn!("Dropping negative operational state value {} of {}", value, key);
+    None
+}
+
+// lower case without the surrounding _ of e.g. __subscription_id__
+fn sanitize(key: &str) -> String{
+    label_name(&key.trim_matches('_').to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn kv(key: &str, value: Value) -> KeyValue{
+        KeyValue{
+            key: key.to_string(),
+            value: Some(value),
+        }
+    }
+
+    fn label_names(metrics: &CollectorMetrics) -> Vec<&str>{
+        let mut names: Vec<&str> = metrics.labels.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_groups_have_the_same_labels(){
+        let base_labels = HashMap::from([("system_id".to_string(), "qfx1".to_string())]);
+        let kv_list = vec![
+            kv("__subscription_id__", Value::StrValue("1".to_string())),
+            kv("packets_sent", Value::UintValue(10)),
+            kv("path", Value::StrValue("/interfaces/".to_string())),
+            kv("packets_sent", Value::UintValue(4)),
+            kv("subscription_id", Value::StrValue("2".to_string())),
+            kv("packets_sent", Value::UintValue(6)),
+            kv("path", Value::StrValue("/interfaces/".to_string())),
+            kv("packets_sent", Value::UintValue(3)),
+        ];
+        let metrics_list = opstate_metrics(&kv_list, &base_labels);
+        assert_eq!(metrics_list.len(), 4);
+        for metrics in &metrics_list{
+            assert_eq!(label_names(metrics), vec!["path", "subscription_id", "system_id"]);
+            assert_eq!(metrics.labels["system_id"], "qfx1");
+        }
+        assert_eq!(metrics_list[0].labels["subscription_id"], "1");
+        assert_eq!(metrics_list[0].labels["path"], "");
+        // a path keeps the subscription it belongs to
+        assert_eq!(metrics_list[1].labels["subscription_id"], "1");
+        assert_eq!(metrics_list[1].labels["path"], "/interfaces/");
+        assert_eq!(metrics_list[1].metrics["opstate_packets_sent"], 4);
+        // the next subscription clears the path
+        assert_eq!(metrics_list[2].labels["subscription_id"], "2");
+        assert_eq!(metrics_list[2].labels["path"], "");
+        // the same path of two subscriptions are distinct series
+        assert_eq!(metrics_list[3].labels["subscription_id"], "2");
+        assert_eq!(metrics_list[3].labels["path"], "/interfaces/");
+        assert_ne!(metrics_list[1].labels, metrics_list[3].labels);
+    }
+
+    #[test]
+    fn test_negative_values_dropped(){
+        let kv_list = vec![
+            kv("drops", Value::IntValue(-1)),
+            kv("delta", Value::SintValue(-5)),
+            kv("rate", Value::DoubleValue(-0.5)),
+            kv("sent", Value::IntValue(7)),
+            kv("received", Value::SintValue(3)),
+        ];
+        let metrics_list = opstate_metrics(&kv_list, &HashMap::new());
+        assert_eq!(metrics_list.len(), 1);
+        let mut names: Vec<&String> = metrics_list[0].metrics.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["opstate_received", "opstate_sent"]);
+        assert_eq!(metrics_list[0].metrics["opstate_sent"], 7);
+    }
+
+    #[test]
+    fn test_sanitize(){
+        assert_eq!(sanitize("__Packets-Sent__"), "packets_sent");
+        assert_eq!(sanitize("5min_rate"), "_5min_rate");
+    }
+}
@@ -1,64 +1,228 @@
+#![allow(clippy::module_inception)]
+use anyhow::Context;
 use clap::Parser;
-use grpc::grpc::Grpc;
-use collector_client::collector_client::CollectorClient;
+use collector_protocol::sender::sender::{Compression, Protocol, Sender};
+use commands::commands::Command;
+use credentials::credentials::{Credentials, CredentialsFile, Secret};
+use manager::manager::Manager;
+use opcommand::opcommand::OpCommand;
+use mapping::mapping::Mapping;
+use udp::udp::UdpReceiver;
+use dialout::dialout::DialOutServer;
+use recorder::recorder::{Client as RecClient, Recorder};
 
 pub mod jnx;
 pub mod grpc;
+#[allow(clippy::doc_lazy_continuation)]
 pub mod gnmi;
 pub mod gnmi_jnpr;
+#[allow(non_camel_case_types, clippy::doc_overindented_list_items)]
 pub mod telemetry;
-pub mod collector;
-pub mod collector_client;
+pub mod manager;
+pub mod credentials;
+pub mod connection;
+pub mod commands;
+pub mod opcommand;
+pub mod mapping;
+pub mod converter;
+pub mod native;
+pub mod udp;
+pub mod gnmi_client;
+pub mod dialout;
+pub mod recording;
+pub mod recorder;
+
+const DEFAULT_CLIENT_ID: &str = "cnm";
 
 #[derive(Parser)]
 pub struct Args{
     #[clap(short, long)]
     config: String,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
 #[derive(serde::Deserialize)]
-struct Config{
+pub struct Config{
+    #[serde(default)]
     devices: Vec<Device>,
     collector: Collector,
+    credentials_file: Option<String>,
+    // receiver for Junos native sensors exported over UDP
+    udp: Option<Udp>,
+    // server for device initiated telemetry
+    dialout: Option<DialOut>,
+    // file all received messages are appended to, see the replay command
+    record: Option<String>,
 }
 
-#[derive(serde::Deserialize)]
-struct Device{
+#[derive(serde::Deserialize, Clone)]
+pub struct DialOut{
+    // address and port to listen on, e.g. 0.0.0.0:50051
     address: String,
-    user: String,
-    password: String,
+    // cert_file and key_file of the server, ca_file verifies the device
+    // certificates
     tls: Tls,
+    #[serde(default)]
+    devices: Vec<DialOutDevice>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct DialOutDevice{
+    // common name or DNS subject alternative name of the device certificate
+    name: String,
+    namespace: String,
+    // paths subscribed once the device connected and their mappings
+    #[serde(default)]
+    paths: Vec<Path>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct Udp{
+    // address and port to listen on, e.g. 0.0.0.0:50000
+    address: String,
+    namespace: String,
+    // sensor paths and their mappings, all supported sensors with the
+    // legacy mapping if empty
+    #[serde(default)]
+    paths: Vec<Path>,
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub struct Device{
+    address: String,
+    user: Option<Secret>,
+    password: Option<Secret>,
+    tls: Option<Tls>,
+    #[serde(default)]
     paths: Vec<Path>,
+    limit_records: Option<i32>,
+    limit_time_seconds: Option<i32>,
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
     namespace: String,
+    // poll the telemetry agent operational state every opstate_interval milliseconds
+    opstate_interval: Option<u64>,
+    // operational commands polled for data which can't be streamed
+    #[serde(default)]
+    commands: Vec<OpCommand>,
+    // client id presented to the device, must be unique per collector
+    // instance as the device evicts subscriptions of a reused client id.
+    // Subscriptions log in with <cid>-<subscription name>.
+    cid: Option<String>,
+}
+
+impl Device{
+    // resolves user and password from the device, the credentials file or the environment
+    pub fn credentials(&self, credentials_file: Option<&str>) -> anyhow::Result<(String, String)>{
+        let file = match credentials_file{
+            Some(path) => CredentialsFile::load(path)?,
+            None => CredentialsFile::default(),
+        };
+        let credentials = Credentials{
+            user: self.user.clone(),
+            password: self.password.clone(),
+        };
+        file.resolve(&self.address, &credentials)
+    }
+
+    pub fn client_id(&self) -> String{
+        self.cid.clone().unwrap_or(DEFAULT_CLIENT_ID.to_string())
+    }
+
+    // the paths of the device form the default subscription, additional
+    // subscriptions are opened independently on the same connection
+    pub fn subscriptions(&self) -> Vec<Subscription>{
+        let mut subscriptions = Vec::new();
+        if !self.paths.is_empty(){
+            subscriptions.push(Subscription{
+                name: "default".to_string(),
+                paths: self.paths.clone(),
+                limit_records: self.limit_records,
+                limit_time_seconds: self.limit_time_seconds,
+            });
+        }
+        subscriptions.extend(self.subscriptions.clone());
+        subscriptions
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Subscription{
+    pub name: String,
+    pub paths: Vec<Path>,
+    // stop the subscription after this many records or seconds
+    pub limit_records: Option<i32>,
+    pub limit_time_seconds: Option<i32>,
 }
 
 #[derive(serde::Deserialize)]
 struct Collector{
     address: String,
+    // older collectors are sent uncompressed with the newest protocol they
+    // serve, see Protocol
+    #[serde(default)]
+    compression: Compression,
+    #[serde(default)]
+    protocol: Protocol,
 }
 
-#[derive(serde::Deserialize)]
+impl Collector{
+    fn sender(&self) -> Sender{
+        Sender::new(self.address.clone())
+            .with_compression(self.compression)
+            .with_protocol(self.protocol)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
 pub struct Tls{
-    pub cert_file: String,
-    pub key_file: String,
-    pub ca_file: String,
-    pub server_name: String,
+    pub cert_file: Option<String>,
+    pub key_file: Option<String>,
+    pub ca_file: Option<String>,
+    pub server_name: Option<String>,
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
 }
 
-#[derive(serde::Deserialize, Debug)]
+impl Tls{
+    pub fn is_empty(&self) -> bool{
+        self.cert_file.is_none() && self.key_file.is_none() && self.ca_file.is_none() && self.server_name.is_none() && !self.insecure_skip_verify
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
 pub struct Path{
     path: String,
     freq: u32,
+    filter: Option<String>,
+    // only send values which changed since the last sample, unchanged values
+    // are resent after max_silent_interval milliseconds
+    #[serde(default)]
+    suppress_unchanged: bool,
+    #[serde(default)]
+    max_silent_interval: u32,
+    #[serde(default)]
+    need_eom: bool,
+    origin: Option<String>,
+    // how key paths are turned into metric names and labels
+    mapping: Option<Mapping>,
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> anyhow::Result<()>{
     env_logger::init();
     let args = Args::parse();
-    let config = std::fs::read_to_string(args.config).unwrap();
-    let config: Config = serde_yaml::from_str(&config).unwrap();
+    let config = read_config(&args.config)?;
+    if let Some(command) = args.command{
+        if let Err(e) = commands::commands::run(command, config).await{
+            log::error!("Command failed: {:?}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let mut jh_list = Vec::new();
-    let col_client = CollectorClient::new(config.collector.address);
+    let col_client = config.collector.sender();
     let col_client_client = col_client.client();
     let jh = tokio::spawn(async move {
         if let Err(e) = col_client.run().await{
@@ -66,18 +230,53 @@ async fn main() {
         }
     });
     jh_list.push(jh);
-    for device in config.devices{
-        let grpc = Grpc::new(device.address.clone(), device.tls, device.user.clone(), device.password.clone(), col_client_client.clone()).await.unwrap();
-        let mut client = grpc.client();
+    let rec_client = match config.record{
+        Some(path) => {
+            let recorder = Recorder::new(path);
+            let rec_client = recorder.client();
+            let jh = tokio::spawn(async move{
+                if let Err(e) = recorder.run().await{
+                    log::error!("Failed to run recorder: {:?}", e);
+                }
+            });
+            jh_list.push(jh);
+            rec_client
+        },
+        None => RecClient::disabled(),
+    };
+    if let Some(udp) = config.udp{
+        let udp_receiver = UdpReceiver::new(udp, col_client_client.clone(), rec_client.clone());
         let jh = tokio::spawn(async move{
-            if let Err(e) = client.subscribe_and_receive(device.paths, device.user, device.password, device.namespace).await{
-                log::error!("Failed to subscribe: {:?}", e);
-            };
+            if let Err(e) = udp_receiver.run().await{
+                log::error!("Failed to run udp receiver: {:?}", e);
+            }
         });
         jh_list.push(jh);
     }
-    
+    if let Some(dialout) = config.dialout{
+        let dialout_server = DialOutServer::new(dialout, col_client_client.clone(), rec_client.clone());
+        let jh = tokio::spawn(async move{
+            if let Err(e) = dialout_server.run().await{
+                log::error!("Failed to run dial-out server: {:?}", e);
+            }
+        });
+        jh_list.push(jh);
+    }
+    let manager = Manager::new(args.config, config.collector.address, config.credentials_file, col_client_client, rec_client);
+    let jh = tokio::spawn(async move{
+        if let Err(e) = manager.run(config.devices).await{
+            log::error!("Failed to run device manager: {:?}", e);
+        }
+    });
+    jh_list.push(jh);
+
 
     futures::future::join_all(jh_list).await;
-    println!("Hello, world! xx");
+    Ok(())
+}
+
+fn read_config(path: &str) -> anyhow::Result<Config>{
+    let config = std::fs::read_to_string(path).with_context(|| format!("Failed to read config {}", path))?;
+    let config: Config = serde_yaml::from_str(&config).with_context(|| format!("Invalid config {}", path))?;
+    Ok(config)
 }
@@ -0,0 +1,227 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::SystemTime;
+use log::{error, info, warn};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use collector_protocol::sender::sender::Client as CollClient;
+use crate::grpc::grpc::Grpc;
+use crate::recorder::recorder::Client as RecClient;
+use crate::{read_config, Device};
+
+// how often the config file modification time is checked, in seconds
+const CHECK_INTERVAL: u64 = 5;
+// how long a stopped session gets to cancel its subscriptions, in seconds
+const STOP_TIMEOUT: u64 = 10;
+
+struct Session{
+    device: Device,
+    // user and password the session logged in with
+    credentials: (String, String),
+    stop_tx: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl Session{
+    fn start(device: Device, credentials: (String, String), collector_client: CollClient, recorder: RecClient) -> Session{
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let handle = tokio::spawn(run_session(device.clone(), credentials.clone(), collector_client, recorder, stop_rx));
+        Session{
+            device,
+            credentials,
+            stop_tx,
+            handle,
+        }
+    }
+
+    async fn stop(self){
+        info!("Stopping session to {}", self.device.address);
+        let _ = self.stop_tx.send(());
+        let mut handle = self.handle;
+        if tokio::time::timeout(tokio::time::Duration::from_secs(STOP_TIMEOUT), &mut handle).await.is_err(){
+            warn!("Session to {} did not stop in time, aborting", self.device.address);
+            handle.abort();
+        }
+    }
+}
+
+pub struct Manager{
+    path: String,
+    collector_address: String,
+    credentials_file: Option<String>,
+    collector_client: CollClient,
+    recorder: RecClient,
+    sessions: Vec<Session>,
+}
+
+impl Manager{
+    pub fn new(path: String, collector_address: String, credentials_file: Option<String>, collector_client: CollClient, recorder: RecClient) -> Manager{
+        Manager{
+            path,
+            collector_address,
+            credentials_file,
+            collector_client,
+            recorder,
+            sessions: Vec::new(),
+        }
+    }
+
+    pub async fn run(mut self, devices: Vec<Device>) -> anyhow::Result<()>{
+        self.update(devices).await;
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL));
+        // changes to the credentials file restart the sessions whose
+        // credentials changed, secrets of the devices are read again on
+        // SIGHUP or when the config file changes
+        let mut modified = self.modified_times();
+        info!("Watching config file {}", self.path);
+        loop{
+            tokio::select! {
+                _ = hangup.recv() => {
+                    info!("Received SIGHUP, reloading config");
+                },
+                _ = interval.tick() => {
+                    let current = self.modified_times();
+                    if current == modified{
+                        continue;
+                    }
+                    modified = current;
+                    info!("Config or credentials file changed, reloading config");
+                }
+            }
+            match read_config(&self.path){
+                Ok(config) => {
+                    if config.collector.address != self.collector_address{
+                        warn!("Changing the collector address requires a restart");
+                    }
+                    self.credentials_file = config.credentials_file;
+                    self.update(config.devices).await;
+                    modified = self.modified_times();
+                },
+                Err(e) => {
+                    error!("Failed to reload config: {:?}", e);
+                }
+            }
+        }
+    }
+
+    fn modified_times(&self) -> (Option<SystemTime>, Option<SystemTime>){
+        (modified_time(&self.path), self.credentials_file.as_deref().and_then(modified_time))
+    }
+
+    // stops sessions of devices which are no longer configured or whose
+    // configuration or credentials changed and starts sessions for new
+    // devices, sessions of unchanged devices keep running
+    async fn update(&mut self, devices: Vec<Device>){
+        let mut devices: Vec<(Device, (String, String))> = devices.into_iter().filter_map(|device| {
+            match device.credentials(self.credentials_file.as_deref()){
+                Ok(credentials) => Some((device, credentials)),
+                Err(e) => {
+                    error!("Failed to resolve credentials for {}: {:?}", device.address, e);
+                    None
+                }
+            }
+        }).collect();
+        let mut sessions = Vec::new();
+        for session in self.sessions.drain(..){
+            if let Some(idx) = devices.iter().position(|(d, c)| *d == session.device && *c == session.credentials){
+                devices.remove(idx);
+                sessions.push(session);
+            } else {
+                session.stop().await;
+            }
+        }
+        for (device, credentials) in devices{
+            info!("Starting session to {}", device.address);
+            sessions.push(Session::start(device, credentials, self.collector_client.clone(), self.recorder.clone()));
+        }
+        self.sessions = sessions;
+    }
+}
+
+async fn run_session(device: Device, (user, password): (String, String), collector_client: CollClient, recorder: RecClient, mut stop_rx: oneshot::Receiver<()>){
+    let grpc = tokio::select! {
+        res = Grpc::new(device.address.clone(), device.tls.clone(), user.clone(), password.clone(), device.client_id(), collector_client.clone(), recorder.clone()) => {
+            match res{
+                Ok(grpc) => grpc,
+                Err(e) => {
+                    error!("Failed to connect to {}: {:?}", device.address, e);
+                    return;
+                }
+            }
+        },
+        _ = &mut stop_rx => {
+            return;
+        }
+    };
+    // every subscription logs in with its own client id so they don't
+    // replace each other, the id of the login is the id of the subscription
+    let mut subscriptions = Vec::new();
+    for subscription in device.subscriptions(){
+        let client_id = format!("{}-{}", device.client_id(), subscription.name);
+        let res = tokio::select! {
+            res = Grpc::new(device.address.clone(), device.tls.clone(), user.clone(), password.clone(), client_id.clone(), collector_client.clone(), recorder.clone()) => res,
+            _ = &mut stop_rx => {
+                return;
+            }
+        };
+        match res{
+            Ok(sub_grpc) => subscriptions.push((subscription, client_id, sub_grpc.client())),
+            Err(e) => error!("Failed to log in subscription {} to {}: {:?}", subscription.name, device.address, e),
+        }
+    }
+    // the system id the device reports in its telemetry data also labels the
+    // operational state and command metrics, a device without subscriptions
+    // is identified by its address
+    let (system_id_tx, system_id_rx) = watch::channel(subscriptions.is_empty().then(|| device.address.clone()));
+    let system_id_tx = Arc::new(system_id_tx);
+    // subscriptions, the operational state poller and command pollers of the device
+    let mut tasks: Vec<Pin<Box<dyn Future<Output = ()> + Send>>> = Vec::new();
+    for (subscription, client_id, mut sub_client) in subscriptions.clone(){
+        let (user, password, namespace) = (user.clone(), password.clone(), device.namespace.clone());
+        let system_id_tx = system_id_tx.clone();
+        tasks.push(Box::pin(async move {
+            info!("Subscribing {} with client id {}", subscription.name, client_id);
+            let name = subscription.name.clone();
+            if let Err(e) = sub_client.subscribe_and_receive(client_id, subscription, user, password, namespace, &system_id_tx).await{
+                error!("Failed to subscribe {}: {:?}", name, e);
+            }
+        }));
+    }
+    if let Some(interval) = device.opstate_interval{
+        let mut opstate_client = grpc.client();
+        let (namespace, system_id) = (device.namespace.clone(), system_id_rx.clone());
+        tasks.push(Box::pin(async move {
+            if let Err(e) = opstate_client.poll_operational_state(interval, namespace, system_id).await{
+                error!("Failed to poll operational state: {:?}", e);
+            }
+        }));
+    }
+    for command in device.commands.clone(){
+        let mut command_client = grpc.client();
+        let (namespace, system_id) = (device.namespace.clone(), system_id_rx.clone());
+        tasks.push(Box::pin(async move {
+            let name = command.command.clone();
+            if let Err(e) = command_client.poll_command(command, namespace, system_id).await{
+                error!("Failed to poll command {}: {:?}", name, e);
+            }
+        }));
+    }
+    drop(system_id_tx);
+    tokio::select! {
+        _ = futures::future::join_all(tasks) => {},
+        _ = &mut stop_rx => {
+            for (subscription, _, mut client) in subscriptions{
+                if let Err(e) = client.cancel_subscription(&subscription.name).await{
+                    error!("Failed to cancel subscription {} on {}: {:?}", subscription.name, device.address, e);
+                }
+            }
+        }
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime>{
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use collector_protocol::names::names::{label_name, metric_name};
+use regex::Regex;
+
+// Declarative mapping of Junos key paths to metric names and labels, set per
+// subscribed path:
+//   mapping:
+//     labels: qualified
+//     name_template: "junos_{path}"
+//     keep:
+//     - "/state/counters/"
+//     drop:
+//     - "carrier-transitions$"
+//     types:
+//     - match: "oper-status$"
+//       type: gauge
+//     enums:
+//     - match: "(oper|admin)-status$"
+//       values: {UP: 1, DOWN: 0}
+//       unknown: 2
+// Without a mapping the legacy profile is used: prefix_<key> and
+// counter_<key> labels and metric names built from the prefix and key path
+// with / replaced by __. Characters not allowed in metric names are replaced
+// by _ in the expanded name_template.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Mapping{
+    #[serde(default)]
+    pub labels: LabelStyle,
+    // placeholders: {prefix} and {key} as in the legacy profile, {path} the
+    // full path without keys and {leaf} the last path element, both with
+    // non alphanumeric characters replaced by _
+    pub name_template: Option<String>,
+    // regexes matched against the full path without keys, e.g.
+    // /interfaces/interface/state/counters/in-octets
+    #[serde(default)]
+    pub keep: Vec<String>,
+    #[serde(default)]
+    pub drop: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<TypeHint>,
+    // numeric values of string states, a string the matching enums don't
+    // know sets the gauge to their unknown value. Strings of paths without
+    // an enum are exported as <name>_info metrics with the string in a
+    // value label, the series of the previous string is set to 0 when it
+    // changes
+    #[serde(default)]
+    pub enums: Vec<EnumHint>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelStyle{
+    // prefix_<key> for keys of the prefix, counter_<key> for keys of the key path
+    #[default]
+    Legacy,
+    // <element>_<key>, e.g. interface_name and queue_queue
+    Qualified,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct TypeHint{
+    #[serde(rename = "match")]
+    pub pattern: String,
+    #[serde(rename = "type")]
+    pub value_type: ValueType,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct EnumHint{
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub values: HashMap<String, u64>,
+    // value of strings missing from values, one more than the largest
+    // value by default
+    pub unknown: Option<u64>,
+}
+
+// numeric value of a string state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnumValue{
+    Mapped(u64),
+    Unknown(u64),
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType{
+    // monotonic counter, exported with a <name>_per_sec rate
+    #[default]
+    Counter,
+    // exported as is
+    Gauge,
+}
+
+const DEFAULT_NAME_TEMPLATE: &str = "{prefix}_{key}";
+
+// a parsed __prefix__ value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prefix{
+    pub name: String,
+    pub path: String,
+    pub labels: HashMap<String, String>,
+}
+
+// a parsed key of a key/value pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key{
+    pub name: String,
+    pub path: String,
+    pub labels: HashMap<String, String>,
+}
+
+pub struct Mapper{
+    label_style: LabelStyle,
+    name_template: String,
+    keep: Vec<Regex>,
+    drop: Vec<Regex>,
+    types: Vec<(Regex, ValueType)>,
+    enums: Vec<(Regex, HashMap<String, u64>, u64)>,
+    label_re: Regex,
+    key_re: Regex,
+}
+
+impl Mapper{
+    pub fn new(mapping: &Mapping) -> anyhow::Result<Mapper>{
+        let compile = |pattern: &String| Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid regex {}: {}", pattern, e));
+        Ok(Mapper{
+            label_style: mapping.labels,
+            name_template: mapping.name_template.clone().unwrap_or(DEFAULT_NAME_TEMPLATE.to_string()),
+            keep: mapping.keep.iter().map(compile).collect::<anyhow::Result<_>>()?,
+            drop: mapping.drop.iter().map(compile).collect::<anyhow::Result<_>>()?,
+            types: mapping.types.iter().map(|t| Ok((compile(&t.pattern)?, t.value_type))).collect::<anyhow::Result<_>>()?,
+            enums: mapping.enums.iter().map(|e| {
+                let unknown = e.unknown.unwrap_or(e.values.values().max().map_or(0, |max| max.saturating_add(1)));
+                Ok((compile(&e.pattern)?, e.values.clone(), unknown))
+            }).collect::<anyhow::Result<_>>()?,
+            label_re: Regex::new(r"\[(.*?=.*?)\]")?,
+            key_re: Regex::new(r"\[(.*?)\]")?,
+        })
+    }
+
+    pub fn prefix(&self, prefix: &str) -> Prefix{
+        let path = self.key_re.replace_all(prefix, "").to_string();
+        let name = path.replace('/', "__").replace('-', "_");
+        let labels = match self.label_style{
+            LabelStyle::Legacy => {
+                let mut labels = HashMap::new();
+                for (key, value) in self.legacy_keys(prefix){
+                    let key = label_name(&format!("prefix_{}", key));
+                    let value = value.replace('\'', "").replace('-', "_");
+                    if !value.is_empty(){
+                        labels.insert(key, value);
+                    }
+                }
+                labels
+            },
+            LabelStyle::Qualified => qualified_keys(prefix).into_iter().collect(),
+        };
+        Prefix{
+            name,
+            path,
+            labels,
+        }
+    }
+
+    pub fn key(&self, key: &str) -> Key{
+        let path = self.key_re.replace_all(key, "").to_string();
+        let name = path.replace('/', "__");
+        let mut labels = HashMap::new();
+        match self.label_style{
+            LabelStyle::Legacy => {
+                for (k, v) in self.legacy_keys(key){
+                    let k = k.replace('\'', "").replace('-', "_");
+                    let v = v.replace('\'', "");
+                    if k.is_empty() || v.is_empty(){
+                        continue;
+                    }
+                    labels.insert(label_name(&format!("counter_{}", k)), v);
+                }
+            },
+            LabelStyle::Qualified => {
+                for (k, v) in qualified_keys(key){
+                    labels.insert(k, v);
+                }
+            },
+        }
+        Key{
+            name,
+            path,
+            labels,
+        }
+    }
+
+    // full path of a key without keys, e.g. /interfaces/interface/state/counters/in-octets
+    pub fn full_path(&self, prefix: &Prefix, key: &Key) -> String{
+        format!("{}/{}", prefix.path.trim_end_matches('/'), key.path.trim_start_matches('/'))
+    }
+
+    pub fn metric_name(&self, prefix: &Prefix, key: &Key) -> String{
+        let full_path = self.full_path(prefix, key);
+        let leaf = full_path.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+        let name = self.name_template
+            .replace("{prefix}", &prefix.name)
+            .replace("{key}", &key.name)
+            .replace("{path}", &sanitize(&full_path))
+            .replace("{leaf}", &sanitize(leaf));
+        metric_name(&name)
+    }
+
+    pub fn keep(&self, full_path: &str) -> bool{
+        let kept = self.keep.is_empty() || self.keep.iter().any(|re| re.is_match(full_path));
+        kept && !self.drop.iter().any(|re| re.is_match(full_path))
+    }
+
+    pub fn value_type(&self, full_path: &str) -> ValueType{
+        self.types.iter()
+            .find(|(re, _)| re.is_match(full_path))
+            .map(|(_, value_type)| *value_type)
+            .unwrap_or_default()
+    }
+
+    // numeric value of a string state from the first enum mapping matching
+    // the full path which knows the string, the unknown value of the first
+    // matching one if none knows it and None for paths without an enum
+    pub fn enum_value(&self, full_path: &str, value: &str) -> Option<EnumValue>{
+        let mut matching = self.enums.iter().filter(|(re, _, _)| re.is_match(full_path)).peekable();
+        let unknown = matching.peek().map(|(_, _, unknown)| *unknown)?;
+        Some(matching
+            .find_map(|(_, values, _)| values.get(value).copied())
+            .map_or(EnumValue::Unknown(unknown), EnumValue::Mapped))
+    }
+
+    fn legacy_keys<'a>(&self, path: &'a str) -> Vec<(&'a str, &'a str)>{
+        let mut keys = Vec::new();
+        for captures in self.label_re.captures_iter(path){
+            if let Some(capture) = captures.get(1){
+                let key_value = capture.as_str().split('=').collect::<Vec<&str>>();
+                if key_value.len() == 2{
+                    keys.push((key_value[0], key_value[1]));
+                }
+            }
+        }
+        keys
+    }
+}
+
+// keys of all path elements as (<element>_<key>, value), e.g.
+// interface[name='et-0/0/0']/queue[queue='8'] yields interface_name and queue_queue
+fn qualified_keys(path: &str) -> Vec<(String, String)>{
+    let mut keys = Vec::new();
+    for element in split_elements(path){
+        let Some(start) = element.find('[') else {
+            continue;
+        };
+        let element_name = &element[..start];
+        for predicate in element[start..].split(['[', ']']).filter(|p| !p.is_empty()){
+            for key_value in predicate.split(" and "){
+                let Some((key, value)) = key_value.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches(['\'', '"']);
+                if value.is_empty(){
+                    continue;
+                }
+                keys.push((label_name(&sanitize(&format!("{}_{}", element_name, key.trim()))), value.to_string()));
+            }
+        }
+    }
+    keys
+}
+
+// splits a path at / outside of key predicates
+pub(crate) fn split_elements(path: &str) -> Vec<&str>{
+    let mut elements = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in path.char_indices(){
+        match c{
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '/' if depth == 0 => {
+                if idx > start{
+                    elements.push(&path[start..idx]);
+                }
+                start = idx + 1;
+            },
+            _ => {}
+        }
+    }
+    if path.len() > start{
+        elements.push(&path[start..]);
+    }
+    elements
+}
+
+// lower case path elements joined by _, e.g. in-octets becomes in_octets
+pub(crate) fn sanitize(name: &str) -> String{
+    name.trim_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
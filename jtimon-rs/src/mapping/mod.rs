@@ -0,0 +1 @@
+pub mod mapping;
\ No newline at end of file
@@ -0,0 +1,322 @@
+// This file is @generated by prost-build.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TelemetryStream {
+    #[prost(string, optional, tag = "1")]
+    pub system_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "2")]
+    pub component_id: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub sub_component_id: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "4")]
+    pub sensor_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "5")]
+    pub sequence_number: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag = "7")]
+    pub version_major: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "8")]
+    pub version_minor: ::core::option::Option<u32>,
+    #[prost(message, optional, tag = "101")]
+    pub enterprise: ::core::option::Option<EnterpriseSensors>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnterpriseSensors {
+    /// extend EnterpriseSensors { optional JuniperNetworksSensors juniperNetworks = 2636; }
+    #[prost(message, optional, tag = "2636")]
+    pub juniper_networks: ::core::option::Option<JuniperNetworksSensors>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JuniperNetworksSensors {
+    /// port.proto
+    #[prost(message, optional, tag = "3")]
+    pub jnpr_interface_ext: ::core::option::Option<Port>,
+    /// firewall.proto
+    #[prost(message, optional, tag = "6")]
+    pub jnpr_firewall_ext: ::core::option::Option<Firewall>,
+    /// logical_port.proto
+    #[prost(message, optional, tag = "7")]
+    pub jnpr_logical_interface_ext: ::core::option::Option<LogicalPort>,
+    /// qmon.proto
+    #[prost(message, optional, tag = "15")]
+    pub jnpr_qmon_ext: ::core::option::Option<QueueMonitor>,
+}
+/// port.proto, /junos/system/linecard/interface/
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Port {
+    #[prost(message, repeated, tag = "1")]
+    pub interface_stats: ::prost::alloc::vec::Vec<InterfaceInfos>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterfaceInfos {
+    #[prost(string, optional, tag = "1")]
+    pub if_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub init_time: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag = "3")]
+    pub snmp_if_index: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "4")]
+    pub parent_ae_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
+    pub ingress_stats: ::core::option::Option<InterfaceStats>,
+    #[prost(message, optional, tag = "6")]
+    pub egress_stats: ::core::option::Option<InterfaceStats>,
+    #[prost(message, optional, tag = "7")]
+    pub ingress_errors: ::core::option::Option<IngressInterfaceErrors>,
+    #[prost(string, optional, tag = "8")]
+    pub if_administration_status: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "9")]
+    pub if_operational_status: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "10")]
+    pub if_description: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "11")]
+    pub if_transitions: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag = "12")]
+    pub if_last_change: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "13")]
+    pub if_high_speed: ::core::option::Option<u32>,
+    #[prost(message, repeated, tag = "14")]
+    pub egress_queue_info: ::prost::alloc::vec::Vec<QueueStats>,
+    #[prost(message, repeated, tag = "15")]
+    pub ingress_queue_info: ::prost::alloc::vec::Vec<QueueStats>,
+    #[prost(message, optional, tag = "16")]
+    pub egress_errors: ::core::option::Option<EgressInterfaceErrors>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterfaceStats {
+    #[prost(uint64, optional, tag = "1")]
+    pub if_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub if_octets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub if_1sec_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub if_1sec_octets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub if_uc_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub if_mc_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub if_bc_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "8")]
+    pub if_error: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "9")]
+    pub if_pause_pkts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "10")]
+    pub if_unknown_proto_pkts: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IngressInterfaceErrors {
+    #[prost(uint64, optional, tag = "1")]
+    pub if_errors: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub if_in_qdrops: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub if_in_frame_errors: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub if_discards: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub if_in_runts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub if_in_l3_incompletes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub if_in_l2chan_errors: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "8")]
+    pub if_in_l2_mismatch_timeouts: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "9")]
+    pub if_in_fifo_errors: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "10")]
+    pub if_in_resource_errors: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EgressInterfaceErrors {
+    #[prost(uint64, optional, tag = "1")]
+    pub if_errors: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub if_discards: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueStats {
+    #[prost(uint32, optional, tag = "1")]
+    pub queue_number: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag = "2")]
+    pub packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub bytes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub tail_drop_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub rl_drop_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub rl_drop_bytes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub red_drop_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "8")]
+    pub red_drop_bytes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "9")]
+    pub avg_buffer_occupancy: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "10")]
+    pub cur_buffer_occupancy: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "11")]
+    pub peak_buffer_occupancy: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "12")]
+    pub allocated_buffer_size: ::core::option::Option<u64>,
+}
+/// logical_port.proto, /junos/system/linecard/interface/logical/usage/
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogicalPort {
+    #[prost(message, repeated, tag = "1")]
+    pub interface_info: ::prost::alloc::vec::Vec<LogicalInterfaceInfo>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogicalInterfaceInfo {
+    #[prost(string, optional, tag = "1")]
+    pub if_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub init_time: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag = "3")]
+    pub snmp_if_index: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "4")]
+    pub parent_ae_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
+    pub ingress_stats: ::core::option::Option<LogicalIngressStats>,
+    #[prost(message, optional, tag = "6")]
+    pub egress_stats: ::core::option::Option<LogicalEgressStats>,
+    #[prost(message, optional, tag = "7")]
+    pub op_state: ::core::option::Option<OperationalState>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogicalIngressStats {
+    #[prost(uint64, optional, tag = "1")]
+    pub if_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub if_octets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub if_ucast_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub if_mcast_packets: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogicalEgressStats {
+    #[prost(uint64, optional, tag = "1")]
+    pub if_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub if_octets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub if_ucast_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    pub if_mcast_packets: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OperationalState {
+    #[prost(string, optional, tag = "1")]
+    pub operational_status: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// firewall.proto, /junos/system/linecard/firewall/
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Firewall {
+    #[prost(message, repeated, tag = "1")]
+    pub firewall_stats: ::prost::alloc::vec::Vec<FirewallStats>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FirewallStats {
+    #[prost(string, optional, tag = "1")]
+    pub filter_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub timestamp: ::core::option::Option<u64>,
+    #[prost(message, repeated, tag = "3")]
+    pub memory_usage: ::prost::alloc::vec::Vec<MemoryUsage>,
+    #[prost(message, repeated, tag = "4")]
+    pub counter_stats: ::prost::alloc::vec::Vec<CounterStats>,
+    #[prost(message, repeated, tag = "5")]
+    pub policer_stats: ::prost::alloc::vec::Vec<PolicerStats>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryUsage {
+    #[prost(string, optional, tag = "1")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub allocated: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CounterStats {
+    #[prost(string, optional, tag = "1")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub bytes: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PolicerStats {
+    #[prost(string, optional, tag = "1")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub out_of_spec_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    pub out_of_spec_bytes: ::core::option::Option<u64>,
+}
+/// qmon.proto, /junos/system/linecard/qmon/
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueMonitor {
+    #[prost(message, repeated, tag = "1")]
+    pub queue_monitor_element_info: ::prost::alloc::vec::Vec<QueueMonitorElement>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueMonitorElement {
+    #[prost(string, optional, tag = "1")]
+    pub if_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub parent_ae_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub queue_monitor_stats_egress: ::core::option::Option<QueueMonitorDirection>,
+    #[prost(message, optional, tag = "4")]
+    pub queue_monitor_stats_ingress: ::core::option::Option<QueueMonitorDirection>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueMonitorDirection {
+    #[prost(message, repeated, tag = "1")]
+    pub queue_monitor_stats_info: ::prost::alloc::vec::Vec<QueueMonitorStats>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueMonitorStats {
+    #[prost(uint32, optional, tag = "1")]
+    pub queue_number: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    pub queue_id: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub peak_buffer_occupancy_bytes: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "4")]
+    pub peak_buffer_occupancy_percent: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag = "5")]
+    pub packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub octets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub tail_drop_packets: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "8")]
+    pub tail_drop_octets: ::core::option::Option<u64>,
+}
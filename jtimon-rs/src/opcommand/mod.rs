@@ -0,0 +1 @@
+pub mod opcommand;
\ No newline at end of file
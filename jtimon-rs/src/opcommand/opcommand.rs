@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use log::warn;
+use serde_json::{Map, Value};
+use collector_protocol::collector::collector::CollectorMetrics;
+
+// An operational command polled periodically through OpCommandGet for data
+// which can't be streamed, e.g.
+//   - command: show interfaces diagnostics optics
+//     interval: 30000
+//     extractors:
+//     - path: interface-information/physical-interface
+//       labels:
+//         interface: name
+//       metrics:
+//         optics_rx_power_uw: optics-diagnostics/laser-rx-optical-power
+//       scale:
+//         optics_rx_power_uw: 1000
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct OpCommand{
+    pub command: String,
+    #[serde(default)]
+    pub format: Format,
+    // milliseconds
+    pub interval: u64,
+    pub extractors: Vec<Extractor>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Format{
+    #[default]
+    Json,
+    Xml,
+}
+
+// path selects the entries of the reply a series is created for, label and
+// metric paths are relative to an entry. Path segments are element names
+// separated by /, lists are traversed implicitly. A label missing in an entry
+// is empty, so all series of an extractor have the same labels.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Extractor{
+    pub path: String,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    pub metrics: HashMap<String, String>,
+    // metric name -> factor the value is multiplied with before it is
+    // converted to an unsigned integer, negative values are dropped
+    #[serde(default)]
+    pub scale: HashMap<String, f64>,
+}
+
+pub fn parse_reply(data: &str, format: Format) -> anyhow::Result<Value>{
+    match format{
+        Format::Json => Ok(serde_json::from_str(data)?),
+        Format::Xml => {
+            let doc = roxmltree::Document::parse(data)?;
+            let root = doc.root_element();
+            let mut map = Map::new();
+            map.insert(root.tag_name().name().to_string(), Value::Array(vec![xml_to_json(root)]));
+            Ok(Value::Object(map))
+        }
+    }
+}
+
+// converts XML into the shape of the Junos JSON output, every element is a
+// list and leaf values are wrapped in {"data": value}
+fn xml_to_json(node: roxmltree::Node) -> Value{
+    let children: Vec<roxmltree::Node> = node.children().filter(|c| c.is_element()).collect();
+    if children.is_empty(){
+        let mut map = Map::new();
+        map.insert("data".to_string(), Value::String(node.text().unwrap_or_default().trim().to_string()));
+        return Value::Object(map);
+    }
+    let mut map = Map::new();
+    for child in children{
+        let entry = map.entry(child.tag_name().name().to_string()).or_insert(Value::Array(Vec::new()));
+        if let Value::Array(list) = entry{
+            list.push(xml_to_json(child));
+        }
+    }
+    Value::Object(map)
+}
+
+pub fn extract_metrics(reply: &Value, extractors: &[Extractor], base_labels: &HashMap<String, String>) -> Vec<CollectorMetrics>{
+    let mut metrics_list = Vec::new();
+    for extractor in extractors{
+        for entry in select(reply, &extractor.path){
+            let mut labels = base_labels.clone();
+            for (label, path) in &extractor.labels{
+                let value = select(entry, path).into_iter().find_map(leaf).unwrap_or_default();
+                labels.insert(label.clone(), value);
+            }
+            let mut metrics = HashMap::new();
+            for (name, path) in &extractor.metrics{
+                let Some(value) = select(entry, path).into_iter().find_map(leaf) else {
+                    continue;
+                };
+                let Ok(value) = value.parse::<f64>() else {
+                    continue;
+                };
+                let factor = extractor.scale.get(name).unwrap_or(&1.0);
+                let value = (value * factor).round();
+                if value < 0.0{
+                    warn!("Dropping negative value {} of {}", value, name);
+                    continue;
+                }
+                metrics.insert(name.clone(), value as u64);
+            }
+            if !metrics.is_empty(){
+                metrics_list.push(CollectorMetrics{
+                    namespace: None,
+                    labels,
+                    metrics,
+                });
+            }
+        }
+    }
+    metrics_list
+}
+
+fn select<'a>(value: &'a Value, path: &str) -> Vec<&'a Value>{
+    let mut current = flatten(value);
+    for segment in path.split('/').filter(|s| !s.is_empty()){
+        current = current.into_iter()
+            .filter_map(|v| v.get(segment))
+            .flat_map(flatten)
+            .collect();
+    }
+    current
+}
+
+fn flatten(value: &Value) -> Vec<&Value>{
+    match value{
+        Value::Array(list) => list.iter().flat_map(flatten).collect(),
+        _ => vec![value],
+    }
+}
+
+fn leaf(value: &Value) -> Option<String>{
+    match value{
+        Value::String(v) => Some(v.trim().to_string()),
+        Value::Number(v) => Some(v.to_string()),
+        Value::Bool(v) => Some((*v as u8).to_string()),
+        Value::Object(map) => map.get("data").and_then(leaf),
+        Value::Array(list) => list.iter().find_map(leaf),
+        Value::Null => None,
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const XML: &str = r#"
+<interface-information>
+  <physical-interface>
+    <name>et-0/0/0</name>
+    <optics-diagnostics>
+      <laser-rx-optical-power>0.512</laser-rx-optical-power>
+      <laser-output-power>-0.100</laser-output-power>
+    </optics-diagnostics>
+  </physical-interface>
+  <physical-interface>
+    <optics-diagnostics>
+      <laser-rx-optical-power>0.250</laser-rx-optical-power>
+    </optics-diagnostics>
+  </physical-interface>
+</interface-information>
+"#;
+
+    fn extractor() -> Extractor{
+        serde_yaml::from_str(r#"
+path: interface-information/physical-interface
+labels:
+  interface: name
+metrics:
+  optics_rx_power_uw: optics-diagnostics/laser-rx-optical-power
+  optics_tx_power_uw: optics-diagnostics/laser-output-power
+scale:
+  optics_rx_power_uw: 1000
+  optics_tx_power_uw: 1000
+"#).unwrap()
+    }
+
+    #[test]
+    fn test_xml_to_json(){
+        let reply = parse_reply(XML, Format::Xml).unwrap();
+        let interfaces = &reply["interface-information"][0]["physical-interface"];
+        assert_eq!(interfaces.as_array().unwrap().len(), 2);
+        assert_eq!(interfaces[0]["name"][0]["data"], "et-0/0/0");
+        assert_eq!(interfaces[0]["optics-diagnostics"][0]["laser-rx-optical-power"][0]["data"], "0.512");
+    }
+
+    #[test]
+    fn test_select(){
+        let reply = parse_reply(XML, Format::Xml).unwrap();
+        assert_eq!(select(&reply, "interface-information/physical-interface").len(), 2);
+        let powers: Vec<String> = select(&reply, "interface-information/physical-interface/optics-diagnostics/laser-rx-optical-power")
+            .into_iter()
+            .filter_map(leaf)
+            .collect();
+        assert_eq!(powers, vec!["0.512", "0.250"]);
+        assert!(select(&reply, "interface-information/logical-interface").is_empty());
+    }
+
+    #[test]
+    fn test_extract_metrics(){
+        let reply = parse_reply(XML, Format::Xml).unwrap();
+        let base_labels = HashMap::from([("system_id".to_string(), "qfx1".to_string())]);
+        let metrics_list = extract_metrics(&reply, &[extractor()], &base_labels);
+        assert_eq!(metrics_list.len(), 2);
+        assert_eq!(metrics_list[0].labels["interface"], "et-0/0/0");
+        assert_eq!(metrics_list[0].labels["system_id"], "qfx1");
+        assert_eq!(metrics_list[0].metrics["optics_rx_power_uw"], 512);
+        // the negative power is dropped
+        assert!(!metrics_list[0].metrics.contains_key("optics_tx_power_uw"));
+        // the missing name is an empty label
+        assert_eq!(metrics_list[1].labels["interface"], "");
+        assert_eq!(metrics_list[1].metrics["optics_rx_power_uw"], 250);
+    }
+
+    #[test]
+    fn test_extract_metrics_json(){
+        let reply = parse_reply(r#"{"interface-information": [{"physical-interface": [{"name": [{"data": "et-0/0/1"}], "optics-diagnostics": [{"laser-rx-optical-power": [{"data": "1.5"}]}]}]}]}"#, Format::Json).unwrap();
+        let metrics_list = extract_metrics(&reply, &[extractor()], &HashMap::new());
+        assert_eq!(metrics_list.len(), 1);
+        assert_eq!(metrics_list[0].labels["interface"], "et-0/0/1");
+        assert_eq!(metrics_list[0].metrics["optics_rx_power_uw"], 1500);
+    }
+}
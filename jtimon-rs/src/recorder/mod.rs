@@ -0,0 +1,2 @@
+pub mod recorder;
+pub mod replay;
\ No newline at end of file
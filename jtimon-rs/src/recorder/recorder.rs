@@ -0,0 +1,272 @@
+use std::io::{BufReader, ErrorKind, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{info, warn};
+use prost::Message;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use crate::gnmi::gnmi::SubscribeResponse;
+use crate::recording::recording::{record, Record, Source};
+use crate::telemetry::telemetry::OpenConfigData;
+
+// records waiting to be written, further records are dropped
+const QUEUED_RECORDS: usize = 1000;
+
+// Appends every received message with its receive time to a recording file
+// which can be fed through the converter again with the replay command.
+// Messages are dropped rather than holding up the receivers when the file
+// can't be written fast enough or the recorder stopped.
+pub struct Recorder{
+    path: String,
+    rx: Receiver<Record>,
+    client: Client,
+}
+
+impl Recorder{
+    pub fn new(path: String) -> Recorder{
+        let (tx, rx) = mpsc::channel(QUEUED_RECORDS);
+        Recorder{
+            path,
+            rx,
+            client: Client{ tx: Some(tx), dropped: Arc::default() },
+        }
+    }
+
+    pub fn client(&self) -> Client{
+        self.client.clone()
+    }
+
+    pub async fn run(self) -> anyhow::Result<()>{
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        info!("Recording to {}", self.path);
+        let mut writer = BufWriter::new(file);
+        let mut rx = self.rx;
+        let dropped = self.client.dropped.clone();
+        drop(self.client);
+        while let Some(record) = rx.recv().await{
+            writer.write_all(&record.encode_length_delimited_to_vec()).await?;
+            // keeps the file complete up to the last received message
+            if rx.is_empty(){
+                writer.flush().await?;
+            }
+        }
+        writer.flush().await?;
+        let dropped = dropped.load(Ordering::Relaxed);
+        if dropped > 0{
+            warn!("Dropped {} records", dropped);
+        }
+        Ok(())
+    }
+}
+
+// A client without a recorder drops all messages so the receivers don't
+// have to care whether recording is enabled.
+#[derive(Clone, Default)]
+pub struct Client{
+    tx: Option<Sender<Record>>,
+    // records dropped by all clients, only the first drop is logged
+    dropped: Arc<AtomicU64>,
+}
+
+impl Client{
+    pub fn disabled() -> Client{
+        Client::default()
+    }
+
+    pub fn open_config_data(&self, source: Source, name: &str, data: &OpenConfigData){
+        self.record(source, name, record::Message::OpenConfigData(data.encode_to_vec()));
+    }
+
+    pub fn subscribe_response(&self, source: Source, name: &str, response: &SubscribeResponse){
+        self.record(source, name, record::Message::SubscribeResponse(response.encode_to_vec()));
+    }
+
+    fn record(&self, source: Source, name: &str, message: record::Message){
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let record = Record{
+            timestamp: now(),
+            source: source.into(),
+            name: name.to_string(),
+            message: Some(message),
+        };
+        let Err(e) = tx.try_send(record) else {
+            return;
+        };
+        if self.dropped.fetch_add(1, Ordering::Relaxed) == 0{
+            match e{
+                TrySendError::Full(_) => warn!("Recording can't keep up, dropping records"),
+                TrySendError::Closed(_) => warn!("Recorder stopped, dropping records"),
+            }
+        }
+    }
+
+    pub fn dropped(&self) -> u64{
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+// reads the records of a recording one at a time
+pub fn read(path: &str) -> anyhow::Result<Records<BufReader<std::fs::File>>>{
+    Ok(Records::new(BufReader::new(std::fs::File::open(path)?)))
+}
+
+// The length delimited records of a recording. A record cut short, e.g. by
+// stopping jtimon-rs while writing, ends the recording.
+pub struct Records<R>{
+    reader: R,
+    count: usize,
+    done: bool,
+}
+
+impl<R: Read> Records<R>{
+    pub fn new(reader: R) -> Records<R>{
+        Records{
+            reader,
+            count: 0,
+            done: false,
+        }
+    }
+
+    // the varint length of the next record, None at the end of the recording
+    fn length(&mut self) -> std::io::Result<Option<u64>>{
+        let mut length = 0;
+        for shift in (0..64).step_by(7){
+            let mut byte = [0];
+            if self.reader.read(&mut byte)? == 0{
+                if shift == 0{
+                    return Ok(None);
+                }
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            length |= ((byte[0] & 0x7f) as u64) << shift;
+            if byte[0] & 0x80 == 0{
+                return Ok(Some(length));
+            }
+        }
+        Err(std::io::Error::new(ErrorKind::InvalidData, "record length too long"))
+    }
+
+    fn next_record(&mut self) -> anyhow::Result<Option<Record>>{
+        let Some(length) = self.length()? else {
+            return Ok(None);
+        };
+        // read up to length bytes, a bogus length doesn't allocate more
+        // than the rest of the file
+        let mut buf = Vec::new();
+        (&mut self.reader).take(length).read_to_end(&mut buf)?;
+        if (buf.len() as u64) < length{
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Some(Record::decode(buf.as_slice())?))
+    }
+}
+
+impl<R: Read> Iterator for Records<R>{
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Record>{
+        if self.done{
+            return None;
+        }
+        match self.next_record(){
+            Ok(Some(record)) => {
+                self.count += 1;
+                Some(record)
+            },
+            Ok(None) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                warn!("Ignoring the end of the recording after {} records: {}", self.count, e);
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+// microseconds since the epoch
+fn now() -> u64{
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::telemetry::{key_value::Value, KeyValue};
+
+    fn data(sequence_number: u64) -> OpenConfigData{
+        OpenConfigData{
+            system_id: "qfx1".to_string(),
+            path: "sensor_1000:/interfaces/:/interfaces/:PFE".to_string(),
+            sequence_number,
+            kv: vec![KeyValue{
+                key: "in-octets".to_string(),
+                value: Some(Value::UintValue(1000 + sequence_number)),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_read(){
+        let path = std::env::temp_dir().join(format!("jtimon-recording-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let _ = std::fs::remove_file(&path);
+        let recorder = Recorder::new(path.clone());
+        let client = recorder.client();
+        let handle = tokio::spawn(recorder.run());
+        client.open_config_data(Source::Device, "127.0.0.1:50051", &data(1));
+        client.subscribe_response(Source::Dialout, "mx1", &SubscribeResponse::default());
+        client.open_config_data(Source::Device, "127.0.0.1:50051", &data(2));
+        drop(client);
+        handle.await.unwrap().unwrap();
+
+        let mut buf = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // a truncated last record is ignored
+        buf.extend_from_slice(&Record{ name: "cut".to_string(), ..Default::default() }.encode_length_delimited_to_vec()[..3]);
+        let records: Vec<Record> = Records::new(buf.as_slice()).collect();
+        assert_eq!(records.len(), 3);
+        assert!(records.windows(2).all(|r| r[0].timestamp <= r[1].timestamp));
+        assert_eq!(records[1].source(), Source::Dialout);
+        assert_eq!(records[1].name, "mx1");
+        let Some(record::Message::OpenConfigData(encoded)) = &records[2].message else {
+            panic!("expected OpenConfigData, got {:?}", records[2].message);
+        };
+        assert_eq!(OpenConfigData::decode(encoded.as_slice()).unwrap(), data(2));
+    }
+
+    #[tokio::test]
+    async fn test_records_dropped(){
+        let path = std::env::temp_dir().join(format!("jtimon-recording-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        // a directory can't be opened for appending
+        let recorder = Recorder::new(path.to_str().unwrap().to_string());
+        let client = recorder.client();
+        assert!(recorder.run().await.is_err());
+        client.open_config_data(Source::Device, "127.0.0.1:50051", &data(1));
+        client.open_config_data(Source::Device, "127.0.0.1:50051", &data(2));
+        assert_eq!(client.dropped(), 2);
+        std::fs::remove_dir(&path).unwrap();
+
+        // a recorder which isn't running fills up and then drops records
+        let recorder = Recorder::new(String::new());
+        let client = recorder.client();
+        for sequence_number in 0..QUEUED_RECORDS as u64 + 10{
+            client.open_config_data(Source::Device, "127.0.0.1:50051", &data(sequence_number));
+        }
+        assert_eq!(client.dropped(), 10);
+    }
+}
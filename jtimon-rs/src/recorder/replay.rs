@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use log::{info, warn};
+use prost::Message;
+use tokio::time::{Duration, Instant};
+use collector_protocol::sender::sender::Client as CollClient;
+use crate::converter::assembler::Assembler;
+use crate::converter::converter::Converter;
+use crate::dialout::dialout::notification_data;
+use crate::gnmi::gnmi::{subscribe_response, SubscribeResponse};
+use crate::recording::recording::{record, Record, Source};
+use crate::telemetry::telemetry::OpenConfigData;
+use crate::udp::udp;
+use crate::{Config, Path};
+
+// the assembler and converter of a recorded device
+struct Pipeline{
+    paths: Vec<Path>,
+    assembler: Assembler,
+    converter: Converter,
+}
+
+// Feeds recorded messages through the assemblers and converters configured
+// for their devices and sends the metrics to the collector. The time between
+// messages is divided by speed, a speed of 0 replays as fast as possible.
+// Returns the number of metrics sent.
+pub async fn replay(config: &Config, records: impl IntoIterator<Item = Record>, speed: f64, collector_client: &CollClient) -> anyhow::Result<usize>{
+    let mut pipelines: HashMap<(Source, String), Option<Pipeline>> = HashMap::new();
+    let mut first = None;
+    let start = Instant::now();
+    let mut sent = 0;
+    for record in records{
+        let first = *first.get_or_insert(record.timestamp);
+        if speed > 0.0{
+            let offset = record.timestamp.saturating_sub(first) as f64 / speed;
+            tokio::time::sleep_until(start + Duration::from_micros(offset as u64)).await;
+        }
+        let source = record.source();
+        let pipeline = pipelines.entry((source, record.name.clone()))
+            .or_insert_with(|| pipeline(config, source, &record.name));
+        let Some(pipeline) = pipeline else {
+            continue;
+        };
+        for data in record_data(&record, pipeline){
+            for collector_metrics in pipeline.converter.convert(&data){
+                collector_client.send(collector_metrics).await?;
+                sent += 1;
+            }
+        }
+    }
+    Ok(sent)
+}
+
+// the complete samples of a record, gNMI notifications are converted to the
+// Junos key/value layout as done by the dial-out server
+fn record_data(record: &Record, pipeline: &mut Pipeline) -> Vec<OpenConfigData>{
+    match &record.message{
+        Some(record::Message::OpenConfigData(encoded)) => {
+            match OpenConfigData::decode(encoded.as_slice()){
+                Ok(data) => pipeline.assembler.add(data).into_iter().collect(),
+                Err(e) => {
+                    warn!("Skipping undecodable record of {}: {:?}", record.name, e);
+                    Vec::new()
+                }
+            }
+        },
+        Some(record::Message::SubscribeResponse(encoded)) => {
+            match SubscribeResponse::decode(encoded.as_slice()){
+                Ok(SubscribeResponse{ response: Some(subscribe_response::Response::Update(notification)), .. }) => {
+                    notification_data(&notification, &record.name, &pipeline.paths)
+                },
+                Ok(_) => Vec::new(),
+                Err(e) => {
+                    warn!("Skipping undecodable record of {}: {:?}", record.name, e);
+                    Vec::new()
+                }
+            }
+        },
+        None => Vec::new(),
+    }
+}
+
+// the pipeline for the current configuration of a recorded device, records
+// of devices which are no longer configured are skipped
+fn pipeline(config: &Config, source: Source, name: &str) -> Option<Pipeline>{
+    let found = match source{
+        Source::Device => config.devices.iter()
+            .find(|d| d.address == name)
+            .map(|d| (d.subscriptions().into_iter().flat_map(|s| s.paths).collect(), d.namespace.clone())),
+        Source::Dialout => config.dialout.iter()
+            .flat_map(|d| d.devices.iter())
+            .find(|d| d.name == name)
+            .map(|d| (d.paths.clone(), d.namespace.clone())),
+        Source::Udp => config.udp.as_ref()
+            .map(|u| (udp::paths(u), u.namespace.clone())),
+    };
+    let Some((paths, namespace)) = found else {
+        warn!("Skipping records of {} {}, not configured", source.as_str_name(), name);
+        return None;
+    };
+    let converter = match Converter::new(&paths, namespace){
+        Ok(converter) => converter,
+        Err(e) => {
+            warn!("Skipping records of {} {}: {:?}", source.as_str_name(), name, e);
+            return None;
+        }
+    };
+    info!("Replaying records of {} {}", source.as_str_name(), name);
+    Some(Pipeline{
+        assembler: Assembler::new(&paths),
+        paths,
+        converter,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::telemetry::{key_value::Value, KeyValue};
+
+    const CONFIG: &str = r#"
+collector:
+  address: 127.0.0.1:50055
+devices:
+- address: 127.0.0.1:50052
+  namespace: qfx
+  paths:
+  - path: /interfaces/
+    freq: 2000
+"#;
+
+    fn record(name: &str, timestamp: u64, sequence_number: u64, in_octets: u64) -> Record{
+        let data = OpenConfigData{
+            system_id: "qfx1".to_string(),
+            component_id: 1,
+            path: "sensor_1000:/interfaces/:/interfaces/:PFE".to_string(),
+            sequence_number,
+            timestamp: timestamp / 1000,
+            kv: vec![
+                KeyValue{ key: "__prefix__".to_string(), value: Some(Value::StrValue("/interfaces/interface[name='et-0/0/0']/".to_string())) },
+                KeyValue{ key: "state/counters/in-octets".to_string(), value: Some(Value::UintValue(in_octets)) },
+            ],
+            ..Default::default()
+        };
+        Record{
+            timestamp,
+            source: Source::Device.into(),
+            name: name.to_string(),
+            message: Some(record::Message::OpenConfigData(data.encode_to_vec())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay(){
+        let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        let records = vec![
+            record("127.0.0.1:50052", 1_000_000, 1, 1000),
+            record("10.0.0.1:50051", 1_500_000, 1, 1000),
+            record("127.0.0.1:50052", 3_000_000, 2, 3000),
+        ];
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let collector_client = CollClient::new(tx, "127.0.0.1:50055".to_string());
+        let sent = replay(&config, records, 0.0, &collector_client).await.unwrap();
+        drop(collector_client);
+        let mut metrics = Vec::new();
+        while let Some(m) = rx.recv().await{
+            metrics.push(m);
+        }
+        // the unconfigured device is skipped
+        assert_eq!(sent, metrics.len());
+        assert!(metrics.iter().all(|m| m.labels.get("system_id").map(String::as_str) == Some("qfx1")));
+        let rate = metrics.iter().rev()
+            .find_map(|m| m.metrics.iter().find(|(k, _)| k.ends_with("_per_sec")).map(|(_, v)| *v))
+            .expect("a rate from the second sample");
+        assert_eq!(rate, 1000);
+    }
+}
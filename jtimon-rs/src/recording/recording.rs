@@ -0,0 +1,60 @@
+// This file is @generated by prost-build.
+/// A message received from a device. Recordings are a sequence of
+/// length-delimited records in the order they were received.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Record {
+    /// receive time in microseconds since the epoch
+    #[prost(uint64, tag = "1")]
+    pub timestamp: u64,
+    #[prost(enumeration = "Source", tag = "2")]
+    pub source: i32,
+    /// address of a configured device, name of a dial-out device or the
+    /// address the native sensors were sent from
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(oneof = "record::Message", tags = "4, 5")]
+    pub message: ::core::option::Option<record::Message>,
+}
+/// Nested message and enum types in `Record`.
+pub mod record {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Message {
+        /// an encoded telemetry.OpenConfigData
+        #[prost(bytes, tag = "4")]
+        OpenConfigData(::prost::alloc::vec::Vec<u8>),
+        /// an encoded gnmi.SubscribeResponse
+        #[prost(bytes, tag = "5")]
+        SubscribeResponse(::prost::alloc::vec::Vec<u8>),
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Source {
+    Device = 0,
+    Dialout = 1,
+    Udp = 2,
+}
+impl Source {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Source::Device => "DEVICE",
+            Source::Dialout => "DIALOUT",
+            Source::Udp => "UDP",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DEVICE" => Some(Self::Device),
+            "DIALOUT" => Some(Self::Dialout),
+            "UDP" => Some(Self::Udp),
+            _ => None,
+        }
+    }
+}
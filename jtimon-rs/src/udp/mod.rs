@@ -0,0 +1,2 @@
+pub mod udp;
+pub mod native;
\ No newline at end of file
@@ -0,0 +1,260 @@
+use crate::native::native::{
+    Firewall, InterfaceStats, LogicalPort, Port, QueueMonitor, QueueMonitorDirection, QueueStats, TelemetryStream,
+};
+use crate::telemetry::telemetry::{key_value::Value, KeyValue, OpenConfigData};
+
+// Junos native sensors are turned into the key/value layout of the gRPC
+// sensors, a __prefix__ per entity followed by its values with keys in
+// brackets, so they go through the same converter and mappings. Key names
+// are the proto field names with _ replaced by -.
+pub fn open_config_data(stream: TelemetryStream) -> OpenConfigData{
+    let mut kvs = KeyValues::default();
+    if let Some(sensors) = stream.enterprise.as_ref().and_then(|e| e.juniper_networks.as_ref()){
+        if let Some(port) = &sensors.jnpr_interface_ext{
+            add_port(&mut kvs, port);
+        }
+        if let Some(logical_port) = &sensors.jnpr_logical_interface_ext{
+            add_logical_port(&mut kvs, logical_port);
+        }
+        if let Some(firewall) = &sensors.jnpr_firewall_ext{
+            add_firewall(&mut kvs, firewall);
+        }
+        if let Some(qmon) = &sensors.jnpr_qmon_ext{
+            add_qmon(&mut kvs, qmon);
+        }
+    }
+    OpenConfigData{
+        system_id: stream.system_id().to_string(),
+        component_id: stream.component_id(),
+        sub_component_id: stream.sub_component_id(),
+        path: stream.sensor_name().to_string(),
+        sequence_number: stream.sequence_number() as u64,
+        timestamp: stream.timestamp(),
+        kv: kvs.0,
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct KeyValues(Vec<KeyValue>);
+
+impl KeyValues{
+    fn prefix(&mut self, prefix: String){
+        self.push("__prefix__".to_string(), Value::StrValue(prefix));
+    }
+    fn uint(&mut self, key: &str, value: Option<u64>){
+        if let Some(value) = value{
+            self.push(key.to_string(), Value::UintValue(value));
+        }
+    }
+    fn str(&mut self, key: &str, value: Option<&String>){
+        if let Some(value) = value{
+            self.push(key.to_string(), Value::StrValue(value.clone()));
+        }
+    }
+    fn push(&mut self, key: String, value: Value){
+        self.0.push(KeyValue{
+            key,
+            value: Some(value),
+        });
+    }
+}
+
+fn add_port(kvs: &mut KeyValues, port: &Port){
+    for info in &port.interface_stats{
+        kvs.prefix(format!("/junos/system/linecard/interface/interface[name='{}']/", info.if_name()));
+        kvs.uint("init-time", info.init_time);
+        kvs.uint("snmp-if-index", info.snmp_if_index.map(u64::from));
+        kvs.str("parent-ae-name", info.parent_ae_name.as_ref());
+        kvs.str("if-administration-status", info.if_administration_status.as_ref());
+        kvs.str("if-operational-status", info.if_operational_status.as_ref());
+        kvs.str("if-description", info.if_description.as_ref());
+        kvs.uint("if-transitions", info.if_transitions);
+        kvs.uint("if-last-change", info.if_last_change.map(u64::from));
+        kvs.uint("if-high-speed", info.if_high_speed.map(u64::from));
+        if let Some(stats) = &info.ingress_stats{
+            add_interface_stats(kvs, "ingress-stats", stats);
+        }
+        if let Some(stats) = &info.egress_stats{
+            add_interface_stats(kvs, "egress-stats", stats);
+        }
+        if let Some(errors) = &info.ingress_errors{
+            kvs.uint("ingress-errors/if-errors", errors.if_errors);
+            kvs.uint("ingress-errors/if-in-qdrops", errors.if_in_qdrops);
+            kvs.uint("ingress-errors/if-in-frame-errors", errors.if_in_frame_errors);
+            kvs.uint("ingress-errors/if-discards", errors.if_discards);
+            kvs.uint("ingress-errors/if-in-runts", errors.if_in_runts);
+            kvs.uint("ingress-errors/if-in-l3-incompletes", errors.if_in_l3_incompletes);
+            kvs.uint("ingress-errors/if-in-l2chan-errors", errors.if_in_l2chan_errors);
+            kvs.uint("ingress-errors/if-in-l2-mismatch-timeouts", errors.if_in_l2_mismatch_timeouts);
+            kvs.uint("ingress-errors/if-in-fifo-errors", errors.if_in_fifo_errors);
+            kvs.uint("ingress-errors/if-in-resource-errors", errors.if_in_resource_errors);
+        }
+        if let Some(errors) = &info.egress_errors{
+            kvs.uint("egress-errors/if-errors", errors.if_errors);
+            kvs.uint("egress-errors/if-discards", errors.if_discards);
+        }
+        for queue in &info.egress_queue_info{
+            add_queue_stats(kvs, "egress-queue-info", queue);
+        }
+        for queue in &info.ingress_queue_info{
+            add_queue_stats(kvs, "ingress-queue-info", queue);
+        }
+    }
+}
+
+fn add_interface_stats(kvs: &mut KeyValues, name: &str, stats: &InterfaceStats){
+    kvs.uint(&format!("{}/if-pkts", name), stats.if_pkts);
+    kvs.uint(&format!("{}/if-octets", name), stats.if_octets);
+    kvs.uint(&format!("{}/if-1sec-pkts", name), stats.if_1sec_pkts);
+    kvs.uint(&format!("{}/if-1sec-octets", name), stats.if_1sec_octets);
+    kvs.uint(&format!("{}/if-uc-pkts", name), stats.if_uc_pkts);
+    kvs.uint(&format!("{}/if-mc-pkts", name), stats.if_mc_pkts);
+    kvs.uint(&format!("{}/if-bc-pkts", name), stats.if_bc_pkts);
+    kvs.uint(&format!("{}/if-error", name), stats.if_error);
+    kvs.uint(&format!("{}/if-pause-pkts", name), stats.if_pause_pkts);
+    kvs.uint(&format!("{}/if-unknown-proto-pkts", name), stats.if_unknown_proto_pkts);
+}
+
+fn add_queue_stats(kvs: &mut KeyValues, name: &str, queue: &QueueStats){
+    let key = format!("{}[queue-number='{}']", name, queue.queue_number());
+    kvs.uint(&format!("{}/packets", key), queue.packets);
+    kvs.uint(&format!("{}/bytes", key), queue.bytes);
+    kvs.uint(&format!("{}/tail-drop-packets", key), queue.tail_drop_packets);
+    kvs.uint(&format!("{}/rl-drop-packets", key), queue.rl_drop_packets);
+    kvs.uint(&format!("{}/rl-drop-bytes", key), queue.rl_drop_bytes);
+    kvs.uint(&format!("{}/red-drop-packets", key), queue.red_drop_packets);
+    kvs.uint(&format!("{}/red-drop-bytes", key), queue.red_drop_bytes);
+    kvs.uint(&format!("{}/avg-buffer-occupancy", key), queue.avg_buffer_occupancy);
+    kvs.uint(&format!("{}/cur-buffer-occupancy", key), queue.cur_buffer_occupancy);
+    kvs.uint(&format!("{}/peak-buffer-occupancy", key), queue.peak_buffer_occupancy);
+    kvs.uint(&format!("{}/allocated-buffer-size", key), queue.allocated_buffer_size);
+}
+
+fn add_logical_port(kvs: &mut KeyValues, logical_port: &LogicalPort){
+    for info in &logical_port.interface_info{
+        kvs.prefix(format!("/junos/system/linecard/interface/logical/usage/interface[name='{}']/", info.if_name()));
+        kvs.uint("init-time", info.init_time);
+        kvs.uint("snmp-if-index", info.snmp_if_index.map(u64::from));
+        kvs.str("parent-ae-name", info.parent_ae_name.as_ref());
+        if let Some(stats) = &info.ingress_stats{
+            kvs.uint("ingress-stats/if-packets", stats.if_packets);
+            kvs.uint("ingress-stats/if-octets", stats.if_octets);
+            kvs.uint("ingress-stats/if-ucast-packets", stats.if_ucast_packets);
+            kvs.uint("ingress-stats/if-mcast-packets", stats.if_mcast_packets);
+        }
+        if let Some(stats) = &info.egress_stats{
+            kvs.uint("egress-stats/if-packets", stats.if_packets);
+            kvs.uint("egress-stats/if-octets", stats.if_octets);
+            kvs.uint("egress-stats/if-ucast-packets", stats.if_ucast_packets);
+            kvs.uint("egress-stats/if-mcast-packets", stats.if_mcast_packets);
+        }
+        if let Some(op_state) = &info.op_state{
+            kvs.str("op-state/operational-status", op_state.operational_status.as_ref());
+        }
+    }
+}
+
+fn add_firewall(kvs: &mut KeyValues, firewall: &Firewall){
+    for stats in &firewall.firewall_stats{
+        kvs.prefix(format!("/junos/system/linecard/firewall/filter[name='{}']/", stats.filter_name()));
+        for memory in &stats.memory_usage{
+            kvs.uint(&format!("memory-usage[name='{}']/allocated", memory.name()), memory.allocated);
+        }
+        for counter in &stats.counter_stats{
+            kvs.uint(&format!("counter-stats[name='{}']/packets", counter.name()), counter.packets);
+            kvs.uint(&format!("counter-stats[name='{}']/bytes", counter.name()), counter.bytes);
+        }
+        for policer in &stats.policer_stats{
+            kvs.uint(&format!("policer-stats[name='{}']/out-of-spec-packets", policer.name()), policer.out_of_spec_packets);
+            kvs.uint(&format!("policer-stats[name='{}']/out-of-spec-bytes", policer.name()), policer.out_of_spec_bytes);
+        }
+    }
+}
+
+fn add_qmon(kvs: &mut KeyValues, qmon: &QueueMonitor){
+    for element in &qmon.queue_monitor_element_info{
+        kvs.prefix(format!("/junos/system/linecard/qmon/interface[name='{}']/", element.if_name()));
+        kvs.str("parent-ae-name", element.parent_ae_name.as_ref());
+        if let Some(egress) = &element.queue_monitor_stats_egress{
+            add_qmon_direction(kvs, "egress", egress);
+        }
+        if let Some(ingress) = &element.queue_monitor_stats_ingress{
+            add_qmon_direction(kvs, "ingress", ingress);
+        }
+    }
+}
+
+fn add_qmon_direction(kvs: &mut KeyValues, direction: &str, stats: &QueueMonitorDirection){
+    for queue in &stats.queue_monitor_stats_info{
+        let key = format!("{}[queue-number='{}']", direction, queue.queue_number());
+        kvs.uint(&format!("{}/queue-id", key), queue.queue_id.map(u64::from));
+        kvs.uint(&format!("{}/peak-buffer-occupancy-bytes", key), queue.peak_buffer_occupancy_bytes.map(u64::from));
+        kvs.uint(&format!("{}/peak-buffer-occupancy-percent", key), queue.peak_buffer_occupancy_percent.map(u64::from));
+        kvs.uint(&format!("{}/packets", key), queue.packets);
+        kvs.uint(&format!("{}/octets", key), queue.octets);
+        kvs.uint(&format!("{}/tail-drop-packets", key), queue.tail_drop_packets);
+        kvs.uint(&format!("{}/tail-drop-octets", key), queue.tail_drop_octets);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+    use prost::Message;
+    use crate::converter::converter::Converter;
+    use crate::native::native::{EnterpriseSensors, InterfaceInfos, JuniperNetworksSensors};
+    use super::*;
+
+    #[test]
+    fn test_port_sensor(){
+        let stream = TelemetryStream{
+            system_id: Some("mx1".to_string()),
+            component_id: Some(1),
+            sensor_name: Some("sensor_1:/junos/system/linecard/interface/:/junos/system/linecard/interface/:PFE".to_string()),
+            sequence_number: Some(7),
+            timestamp: Some(1000),
+            enterprise: Some(EnterpriseSensors{
+                juniper_networks: Some(JuniperNetworksSensors{
+                    jnpr_interface_ext: Some(Port{
+                        interface_stats: vec![InterfaceInfos{
+                            if_name: Some("xe-0/0/0".to_string()),
+                            if_operational_status: Some("UP".to_string()),
+                            ingress_stats: Some(InterfaceStats{
+                                if_pkts: Some(10),
+                                ..Default::default()
+                            }),
+                            egress_queue_info: vec![QueueStats{
+                                queue_number: Some(3),
+                                packets: Some(5),
+                                ..Default::default()
+                            }],
+                            ..Default::default()
+                        }],
+                    }),
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        };
+        // decode what a device would send
+        let stream = TelemetryStream::decode(stream.encode_to_vec().as_slice()).unwrap();
+        let data = open_config_data(stream);
+        assert_eq!(data.system_id, "mx1");
+        assert_eq!(data.sequence_number, 7);
+
+        let paths: Vec<crate::Path> = serde_yaml::from_str(r#"
+- path: /junos/system/linecard/interface/
+  freq: 0
+  mapping:
+    enums:
+    - match: "status$"
+      values: {UP: 1}
+"#).unwrap();
+        let mut converter = Converter::new(&paths, "mx".to_string()).unwrap();
+        let metrics: HashMap<String, u64> = converter.convert(&data).into_iter().flat_map(|m| m.metrics).collect();
+        assert_eq!(metrics.get("__junos__system__linecard__interface__interface___if_operational_status"), Some(&1));
+        assert_eq!(metrics.get("__junos__system__linecard__interface__interface___ingress_stats__if_pkts"), Some(&10));
+        assert_eq!(metrics.get("__junos__system__linecard__interface__interface___egress_queue_info__packets"), Some(&5));
+    }
+}
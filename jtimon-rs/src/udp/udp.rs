@@ -0,0 +1,97 @@
+use log::{error, info, warn};
+use prost::Message;
+use tokio::net::UdpSocket;
+use collector_protocol::sender::sender::Client as CollClient;
+use crate::converter::assembler::Assembler;
+use crate::converter::converter::Converter;
+use crate::native::native::TelemetryStream;
+use crate::recorder::recorder::Client as RecClient;
+use crate::recording::recording::Source;
+use crate::{Path, Udp};
+use super::native::open_config_data;
+
+// largest UDP payload
+const MAX_DATAGRAM_SIZE: usize = 65535;
+
+// sensors decoded from TelemetryStream, used when no paths are configured
+const DEFAULT_PATHS: [&str; 4] = [
+    "/junos/system/linecard/interface/",
+    "/junos/system/linecard/interface/logical/usage/",
+    "/junos/system/linecard/firewall/",
+    "/junos/system/linecard/qmon/",
+];
+
+// Receives Junos native sensors exported over UDP. Every datagram holds one
+// TelemetryStream message, the sensor name has the same
+// <sensor name>:<subscribed path>:<path>:<component> layout as the gRPC
+// sensors and selects the configured path and its mapping.
+pub struct UdpReceiver{
+    config: Udp,
+    collector_client: CollClient,
+    recorder: RecClient,
+}
+
+impl UdpReceiver{
+    pub fn new(config: Udp, collector_client: CollClient, recorder: RecClient) -> UdpReceiver{
+        UdpReceiver{
+            config,
+            collector_client,
+            recorder,
+        }
+    }
+
+    pub async fn run(self) -> anyhow::Result<()>{
+        let paths = paths(&self.config);
+        let mut assembler = Assembler::new(&paths);
+        let mut converter = Converter::new(&paths, self.config.namespace.clone())?;
+        let socket = UdpSocket::bind(&self.config.address).await?;
+        info!("Listening for native sensors on udp {}", self.config.address);
+        let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+        loop{
+            let (len, peer) = match socket.recv_from(&mut buf).await{
+                Ok(res) => res,
+                Err(e) => {
+                    error!("Failed to receive: {:?}", e);
+                    continue;
+                }
+            };
+            let stream = match TelemetryStream::decode(&buf[..len]){
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Failed to decode datagram from {}: {:?}", peer, e);
+                    continue;
+                }
+            };
+            let data = open_config_data(stream);
+            self.recorder.open_config_data(Source::Udp, &peer.to_string(), &data);
+            let Some(sample) = assembler.add(data) else {
+                continue;
+            };
+            for collector_metrics in converter.convert(&sample){
+                self.collector_client.send(collector_metrics).await?;
+            }
+        }
+    }
+}
+
+// the configured paths or the default sensors if none are configured
+pub fn paths(config: &Udp) -> Vec<Path>{
+    if config.paths.is_empty(){
+        default_paths()
+    } else {
+        config.paths.clone()
+    }
+}
+
+fn default_paths() -> Vec<Path>{
+    DEFAULT_PATHS.iter().map(|path| Path{
+        path: path.to_string(),
+        freq: 0,
+        filter: None,
+        suppress_unchanged: false,
+        max_silent_interval: 0,
+        need_eom: false,
+        origin: None,
+        mapping: None,
+    }).collect()
+}
@@ -0,0 +1,107 @@
+// End-to-end test: jtimon-rs subscribes to the mock Junos server and sends
+// the converted metrics to an in-process collector-server, whose /metrics
+// endpoint is scraped until the expected series show up.
+use std::time::Duration;
+use collector_server::grpc_server::grpc_server::GrpcServer;
+use collector_server::prometheus::prometheus::Prometheus;
+use mock_junos::mock::mock::MockServer;
+use mock_junos::script::script::Script;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::process::Command;
+
+const SCRIPT: &str = r#"
+user: USER
+password: PWD
+interval: 200
+repeat: true
+messages:
+- system_id: qfx1
+  path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+  kv:
+  - key: __prefix__
+    str: "/interfaces/interface[name='et-0/0/0']/"
+  - key: state/counters/in-octets
+    uint: 1000
+    increment: 100
+  - key: state/oper-status
+    str: UP
+"#;
+
+fn free_address() -> String{
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+async fn scrape(address: &str) -> anyhow::Result<String>{
+    let mut stream = TcpStream::connect(address).await?;
+    stream.write_all(format!("GET /metrics HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", address).as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+#[tokio::test]
+async fn test_subscription_to_metrics(){
+    let script: Script = serde_yaml::from_str(SCRIPT).unwrap();
+    let device_address = MockServer::new(script).spawn().await.unwrap();
+
+    let grpc_address = free_address();
+    let prometheus_address = free_address();
+    let prom_server = Prometheus::new(prometheus_address.clone());
+    let g_server = GrpcServer::new(grpc_address.clone(), prom_server.client());
+    tokio::spawn(async move { prom_server.web_server().await });
+    tokio::spawn(async move { g_server.run().await });
+
+    let config = format!(r#"
+collector:
+  address: {}
+devices:
+- address: {}
+  namespace: "qfx"
+  user: USER
+  password: PWD
+  paths:
+  - path: /interfaces/
+    freq: 1000
+    mapping:
+      enums:
+      - match: "oper-status$"
+        values: {{UP: 1, DOWN: 0}}
+"#, grpc_address, device_address);
+    let config_path = std::env::temp_dir().join(format!("jtimon-e2e-{}.yaml", std::process::id()));
+    std::fs::write(&config_path, config).unwrap();
+    let mut jtimon = Command::new(env!("CARGO_BIN_EXE_jtimon-rs"))
+        .arg("--config")
+        .arg(&config_path)
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    let expected = [
+        r#"__interfaces__interface___state__counters__in_octets{namespace="qfx",prefix_name="et_0/0/0",system_id="qfx1"}"#,
+        r#"__interfaces__interface___state__counters__in_octets_per_sec{namespace="qfx",prefix_name="et_0/0/0",system_id="qfx1"}"#,
+        r#"__interfaces__interface___state__oper_status{namespace="qfx",prefix_name="et_0/0/0",system_id="qfx1"} 1"#,
+    ];
+    let mut metrics = String::new();
+    for _ in 0..60{
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        metrics = scrape(&prometheus_address).await.unwrap_or_default();
+        if expected.iter().all(|e| metrics.contains(e)){
+            break;
+        }
+    }
+    jtimon.kill().await.unwrap();
+    let _ = std::fs::remove_file(&config_path);
+    for e in expected{
+        assert!(metrics.contains(e), "{} not found in\n{}", e, metrics);
+    }
+    // the device timestamps of two messages are an interval apart, the
+    // octets an increment
+    let rate: f64 = metrics.lines()
+        .find(|l| l.starts_with(expected[1]))
+        .and_then(|l| l.rsplit(' ').next())
+        .and_then(|v| v.parse().ok())
+        .unwrap();
+    assert_eq!(rate, 100.0 * 1000.0 / 200.0);
+}
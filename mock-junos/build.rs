@@ -0,0 +1,30 @@
+fn main() {
+    tonic_build::configure()
+    .out_dir("src/jnx")
+    .include_file("mod.rs")
+    .compile(
+        &["../jtimon-rs/protos/jnx/jnx_authentication_service.proto",
+        "../jtimon-rs/protos/jnx/jnx_common_base_types.proto"],
+        &["../jtimon-rs/protos/jnx"]
+    )
+    .unwrap();
+
+    tonic_build::configure()
+    .out_dir("src/gnmi")
+    .include_file("mod.rs")
+    .compile(
+        &["../jtimon-rs/protos/gnmi/gnmi.proto",
+        "../jtimon-rs/protos/gnmi/gnmi_ext.proto"],
+        &["../jtimon-rs/protos/gnmi"]
+    )
+    .unwrap();
+
+    tonic_build::configure()
+    .out_dir("src/telemetry")
+    .include_file("mod.rs")
+    .compile(
+        &["../jtimon-rs/protos/telemetry/telemetry.proto"],
+        &["../jtimon-rs/protos/telemetry"]
+    )
+    .unwrap();
+}
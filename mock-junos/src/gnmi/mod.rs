@@ -0,0 +1,7 @@
+// This file is @generated by prost-build.
+pub mod gnmi {
+    include!("gnmi.rs");
+}
+pub mod gnmi_ext {
+    include!("gnmi_ext.rs");
+}
@@ -1,33 +1,44 @@
 // This file is @generated by prost-build.
+/// \[brief\]: Request message representing client login credentials
+/// \[detail\]: Request message representing client login credentials
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CollectorMetrics {
-    #[prost(string, optional, tag = "1")]
-    pub namespace: ::core::option::Option<::prost::alloc::string::String>,
-    #[prost(map = "string, string", tag = "2")]
-    pub labels: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    #[prost(map = "string, uint64", tag = "3")]
-    pub metrics: ::std::collections::HashMap<::prost::alloc::string::String, u64>,
+pub struct LoginRequest {
+    /// \[brief\]: Client User name
+    #[prost(string, tag = "1")]
+    pub username: ::prost::alloc::string::String,
+    /// \[brief\]: Client password
+    #[prost(string, tag = "2")]
+    pub password: ::prost::alloc::string::String,
+    /// \[brief\]: Client ID
+    #[prost(string, tag = "3")]
+    pub client_id: ::prost::alloc::string::String,
+    /// \[brief\]: Group ID
+    #[prost(string, tag = "4")]
+    pub group_id: ::prost::alloc::string::String,
 }
+/// \[brief\]: Response message representing the status of the LoginCheck RPC.
+/// \[detail\]: Response message representing the status of the LoginCheck RPC.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Reply {
-    #[prost(string, tag = "1")]
-    pub message: ::prost::alloc::string::String,
+pub struct LoginResponse {
+    /// \[brief\]: Login status
+    #[prost(message, optional, tag = "1")]
+    pub status: ::core::option::Option<super::common::RpcStatus>,
 }
 /// Generated client implementations.
-pub mod collector_server_client {
+pub mod authentication_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    /// [brief]: Authentication service for Junos APIs
+    /// [detail]: Authentication service to allow a client endpoint to authenticate
+    /// itself to gain access to Junos APIs
     #[derive(Debug, Clone)]
-    pub struct CollectorServerClient<T> {
+    pub struct AuthenticationClient<T> {
         inner: tonic::client::Grpc<T>,
     }
-    impl CollectorServerClient<tonic::transport::Channel> {
+    impl AuthenticationClient<tonic::transport::Channel> {
         /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
@@ -38,7 +49,7 @@ pub mod collector_server_client {
             Ok(Self::new(conn))
         }
     }
-    impl<T> CollectorServerClient<T>
+    impl<T> AuthenticationClient<T>
     where
         T: tonic::client::GrpcService<tonic::body::BoxBody>,
         T::Error: Into<StdError>,
@@ -56,7 +67,7 @@ pub mod collector_server_client {
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
-        ) -> CollectorServerClient<InterceptedService<T, F>>
+        ) -> AuthenticationClient<InterceptedService<T, F>>
         where
             F: tonic::service::Interceptor,
             T::ResponseBody: Default,
@@ -70,7 +81,7 @@ pub mod collector_server_client {
                 http::Request<tonic::body::BoxBody>,
             >>::Error: Into<StdError> + Send + Sync,
         {
-            CollectorServerClient::new(InterceptedService::new(inner, interceptor))
+            AuthenticationClient::new(InterceptedService::new(inner, interceptor))
         }
         /// Compress requests with the given encoding.
         ///
@@ -103,10 +114,13 @@ pub mod collector_server_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
-        pub async fn send_metrics(
+        /// [brief]: Verify client credentials
+        /// [detail]: Verify client credentials by checking passed in
+        /// username/password against what is registered with the Junos device
+        pub async fn login(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<Message = super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::LoginRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoginResponse>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -118,55 +132,37 @@ pub mod collector_server_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/collector.CollectorServer/SendMetrics",
-            );
-            let mut req = request.into_streaming_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("collector.CollectorServer", "SendMetrics"));
-            self.inner.client_streaming(req, path, codec).await
-        }
-        pub async fn register_metrics(
-            &mut self,
-            request: impl tonic::IntoRequest<super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::new(
-                        tonic::Code::Unknown,
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/collector.CollectorServer/RegisterMetrics",
+                "/jnx.jet.authentication.Authentication/Login",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("collector.CollectorServer", "RegisterMetrics"));
+                .insert(
+                    GrpcMethod::new("jnx.jet.authentication.Authentication", "Login"),
+                );
             self.inner.unary(req, path, codec).await
         }
     }
 }
 /// Generated server implementations.
-pub mod collector_server_server {
+pub mod authentication_server {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with CollectorServerServer.
+    /// Generated trait containing gRPC methods that should be implemented for use with AuthenticationServer.
     #[async_trait]
-    pub trait CollectorServer: Send + Sync + 'static {
-        async fn send_metrics(
-            &self,
-            request: tonic::Request<tonic::Streaming<super::CollectorMetrics>>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status>;
-        async fn register_metrics(
+    pub trait Authentication: Send + Sync + 'static {
+        /// [brief]: Verify client credentials
+        /// [detail]: Verify client credentials by checking passed in
+        /// username/password against what is registered with the Junos device
+        async fn login(
             &self,
-            request: tonic::Request<super::CollectorMetrics>,
-        ) -> std::result::Result<tonic::Response<super::Reply>, tonic::Status>;
+            request: tonic::Request<super::LoginRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoginResponse>, tonic::Status>;
     }
+    /// [brief]: Authentication service for Junos APIs
+    /// [detail]: Authentication service to allow a client endpoint to authenticate
+    /// itself to gain access to Junos APIs
     #[derive(Debug)]
-    pub struct CollectorServerServer<T: CollectorServer> {
+    pub struct AuthenticationServer<T: Authentication> {
         inner: _Inner<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
@@ -174,7 +170,7 @@ pub mod collector_server_server {
         max_encoding_message_size: Option<usize>,
     }
     struct _Inner<T>(Arc<T>);
-    impl<T: CollectorServer> CollectorServerServer<T> {
+    impl<T: Authentication> AuthenticationServer<T> {
         pub fn new(inner: T) -> Self {
             Self::from_arc(Arc::new(inner))
         }
@@ -226,9 +222,9 @@ pub mod collector_server_server {
             self
         }
     }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for CollectorServerServer<T>
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for AuthenticationServer<T>
     where
-        T: CollectorServer,
+        T: Authentication,
         B: Body + Send + 'static,
         B::Error: Into<StdError> + Send + 'static,
     {
@@ -244,74 +240,24 @@ pub mod collector_server_server {
         fn call(&mut self, req: http::Request<B>) -> Self::Future {
             let inner = self.inner.clone();
             match req.uri().path() {
-                "/collector.CollectorServer/SendMetrics" => {
-                    #[allow(non_camel_case_types)]
-                    struct SendMetricsSvc<T: CollectorServer>(pub Arc<T>);
-                    impl<
-                        T: CollectorServer,
-                    > tonic::server::ClientStreamingService<super::CollectorMetrics>
-                    for SendMetricsSvc<T> {
-                        type Response = super::Reply;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
-                        fn call(
-                            &mut self,
-                            request: tonic::Request<
-                                tonic::Streaming<super::CollectorMetrics>,
-                            >,
-                        ) -> Self::Future {
-                            let inner = Arc::clone(&self.0);
-                            let fut = async move {
-                                <T as CollectorServer>::send_metrics(&inner, request).await
-                            };
-                            Box::pin(fut)
-                        }
-                    }
-                    let accept_compression_encodings = self.accept_compression_encodings;
-                    let send_compression_encodings = self.send_compression_encodings;
-                    let max_decoding_message_size = self.max_decoding_message_size;
-                    let max_encoding_message_size = self.max_encoding_message_size;
-                    let inner = self.inner.clone();
-                    let fut = async move {
-                        let inner = inner.0;
-                        let method = SendMetricsSvc(inner);
-                        let codec = tonic::codec::ProstCodec::default();
-                        let mut grpc = tonic::server::Grpc::new(codec)
-                            .apply_compression_config(
-                                accept_compression_encodings,
-                                send_compression_encodings,
-                            )
-                            .apply_max_message_size_config(
-                                max_decoding_message_size,
-                                max_encoding_message_size,
-                            );
-                        let res = grpc.client_streaming(method, req).await;
-                        Ok(res)
-                    };
-                    Box::pin(fut)
-                }
-                "/collector.CollectorServer/RegisterMetrics" => {
+                "/jnx.jet.authentication.Authentication/Login" => {
                     #[allow(non_camel_case_types)]
-                    struct RegisterMetricsSvc<T: CollectorServer>(pub Arc<T>);
+                    struct LoginSvc<T: Authentication>(pub Arc<T>);
                     impl<
-                        T: CollectorServer,
-                    > tonic::server::UnaryService<super::CollectorMetrics>
-                    for RegisterMetricsSvc<T> {
-                        type Response = super::Reply;
+                        T: Authentication,
+                    > tonic::server::UnaryService<super::LoginRequest> for LoginSvc<T> {
+                        type Response = super::LoginResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CollectorMetrics>,
+                            request: tonic::Request<super::LoginRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CollectorServer>::register_metrics(&inner, request)
-                                    .await
+                                <T as Authentication>::login(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -323,7 +269,7 @@ pub mod collector_server_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = RegisterMetricsSvc(inner);
+                        let method = LoginSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -354,7 +300,7 @@ pub mod collector_server_server {
             }
         }
     }
-    impl<T: CollectorServer> Clone for CollectorServerServer<T> {
+    impl<T: Authentication> Clone for AuthenticationServer<T> {
         fn clone(&self) -> Self {
             let inner = self.inner.clone();
             Self {
@@ -366,7 +312,7 @@ pub mod collector_server_server {
             }
         }
     }
-    impl<T: CollectorServer> Clone for _Inner<T> {
+    impl<T: Authentication> Clone for _Inner<T> {
         fn clone(&self) -> Self {
             Self(Arc::clone(&self.0))
         }
@@ -376,7 +322,7 @@ pub mod collector_server_server {
             write!(f, "{:?}", self.0)
         }
     }
-    impl<T: CollectorServer> tonic::server::NamedService for CollectorServerServer<T> {
-        const NAME: &'static str = "collector.CollectorServer";
+    impl<T: Authentication> tonic::server::NamedService for AuthenticationServer<T> {
+        const NAME: &'static str = "jnx.jet.authentication.Authentication";
     }
 }
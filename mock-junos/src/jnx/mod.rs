@@ -0,0 +1,11 @@
+// This file is @generated by prost-build.
+pub mod jnx {
+    pub mod jet {
+        pub mod authentication {
+            include!("jnx.jet.authentication.rs");
+        }
+        pub mod common {
+            include!("jnx.jet.common.rs");
+        }
+    }
+}
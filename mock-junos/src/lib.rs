@@ -0,0 +1,8 @@
+#![allow(clippy::module_inception)]
+pub mod jnx;
+#[allow(clippy::doc_lazy_continuation)]
+pub mod gnmi;
+#[allow(non_camel_case_types, clippy::doc_overindented_list_items)]
+pub mod telemetry;
+pub mod script;
+pub mod mock;
@@ -0,0 +1,19 @@
+use clap::Parser;
+use mock_junos::mock::mock::MockServer;
+use mock_junos::script::script::Script;
+
+#[derive(Parser)]
+pub struct Args{
+    #[clap(short, long, default_value = "0.0.0.0:50051")]
+    address: String,
+    #[clap(short, long)]
+    script: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()>{
+    env_logger::init();
+    let args = Args::parse();
+    let script = Script::read(&args.script)?;
+    MockServer::new(script).serve(&args.address).await
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use log::{error, info};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
+use crate::gnmi::gnmi::{
+    g_nmi_server::{GNmi, GNmiServer}, subscribe_response, CapabilityRequest, CapabilityResponse, Encoding,
+    GetRequest, GetResponse, ModelData, SetRequest, SetResponse, SubscribeRequest, SubscribeResponse,
+};
+use crate::jnx::jnx::jet::authentication::{
+    authentication_server::{Authentication, AuthenticationServer}, LoginRequest, LoginResponse,
+};
+use crate::jnx::jnx::jet::common::{RpcStatus, StatusCode};
+use crate::script::script::Script;
+use crate::telemetry::telemetry::{
+    key_value, open_config_telemetry_server::{OpenConfigTelemetry, OpenConfigTelemetryServer},
+    CancelSubscriptionReply, CancelSubscriptionRequest, DataEncodingReply, DataEncodingRequest, EncodingType,
+    GetOperationalStateReply, GetOperationalStateRequest, GetSubscriptionsReply, GetSubscriptionsRequest,
+    KeyValue, OpenConfigData, ReturnCode, SubscriptionReply, SubscriptionRequest, SubscriptionResponse,
+};
+
+// A Junos device for tests: accepts Login, OpenConfigTelemetry and gNMI
+// subscriptions and streams the messages of its script.
+#[derive(Clone)]
+pub struct MockServer{
+    script: Arc<Script>,
+    // the open OpenConfigTelemetry subscriptions, a cancelled subscription
+    // ends its stream
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    // serves TLS instead of plaintext if set
+    tls: Option<ServerTlsConfig>,
+}
+
+#[derive(Default)]
+struct Subscriptions{
+    last_id: u32,
+    replies: HashMap<u32, SubscriptionReply>,
+}
+
+impl MockServer{
+    pub fn new(script: Script) -> MockServer{
+        MockServer{
+            script: Arc::new(script),
+            subscriptions: Arc::default(),
+            tls: None,
+        }
+    }
+
+    // the certificate and key of the device, client certificates are
+    // required and verified against client_ca if set
+    pub fn with_tls(mut self, cert: &[u8], key: &[u8], client_ca: Option<&[u8]>) -> MockServer{
+        let mut tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(client_ca) = client_ca{
+            tls = tls.client_ca_root(Certificate::from_pem(client_ca));
+        }
+        self.tls = Some(tls);
+        self
+    }
+
+    pub async fn serve(self, address: &str) -> anyhow::Result<()>{
+        let listener = TcpListener::bind(address).await?;
+        self.serve_listener(listener).await
+    }
+
+    // serves on a free port of localhost in the background and returns the
+    // address
+    pub async fn spawn(self) -> anyhow::Result<SocketAddr>{
+        self.spawn_on("127.0.0.1:0").await
+    }
+
+    // like spawn on the given address, e.g. [::1]:0
+    pub async fn spawn_on(self, address: &str) -> anyhow::Result<SocketAddr>{
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move{
+            if let Err(e) = self.serve_listener(listener).await{
+                error!("Mock server failed: {:?}", e);
+            }
+        });
+        Ok(address)
+    }
+
+    async fn serve_listener(self, listener: TcpListener) -> anyhow::Result<()>{
+        info!("Mock server listening on {}", listener.local_addr()?);
+        let mut server = Server::builder();
+        if let Some(tls) = self.tls.clone(){
+            server = server.tls_config(tls)?;
+        }
+        server
+            .add_service(AuthenticationServer::new(self.clone()))
+            .add_service(OpenConfigTelemetryServer::new(self.clone()))
+            .add_service(GNmiServer::new(self))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await?;
+        Ok(())
+    }
+
+    fn check_metadata<T>(&self, request: &Request<T>) -> Result<(), Box<Status>>{
+        let get = |key: &str| request.metadata().get(key).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+        if self.script.authenticate(&get("username"), &get("password")){
+            Ok(())
+        } else {
+            Err(Box::new(Status::unauthenticated("invalid username or password")))
+        }
+    }
+}
+
+fn now() -> Duration{
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[tonic::async_trait]
+impl Authentication for MockServer{
+    async fn login(&self, request: Request<LoginRequest>) -> Result<Response<LoginResponse>, Status>{
+        let login = request.into_inner();
+        let status = if self.script.authenticate(&login.username, &login.password){
+            info!("Login of {} with client id {}", login.username, login.client_id);
+            RpcStatus{
+                code: StatusCode::Success.into(),
+                message: String::new(),
+            }
+        } else {
+            RpcStatus{
+                code: StatusCode::Failure.into(),
+                message: "invalid username or password".to_string(),
+            }
+        };
+        Ok(Response::new(LoginResponse{
+            status: Some(status),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl OpenConfigTelemetry for MockServer{
+    type telemetrySubscribeStream = ReceiverStream<Result<OpenConfigData, Status>>;
+
+    async fn telemetry_subscribe(&self, request: Request<SubscriptionRequest>) -> Result<Response<Self::telemetrySubscribeStream>, Status>{
+        let path_list = request.into_inner().path_list;
+        let paths: Vec<String> = path_list.iter().map(|p| p.path.clone()).collect();
+        let id = {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            subscriptions.last_id += 1;
+            let id = subscriptions.last_id;
+            subscriptions.replies.insert(id, SubscriptionReply{
+                response: Some(SubscriptionResponse{ subscription_id: id }),
+                path_list,
+            });
+            id
+        };
+        info!("Subscription {} to {:?}", id, paths);
+        let subscriptions = self.subscriptions.clone();
+        let messages: Vec<_> = self.script.messages.iter()
+            .filter(|m| paths.iter().any(|p| p == m.subscribed_path()))
+            .cloned()
+            .collect();
+        let script = self.script.clone();
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move{
+            let mut sequence_numbers: HashMap<(u32, String), u64> = HashMap::new();
+            let mut round = 0;
+            // the device clock advances by the interval with every message,
+            // rates computed from its timestamps don't depend on scheduling
+            let mut clock = now();
+            loop{
+                for message in &messages{
+                    if !subscriptions.lock().unwrap().replies.contains_key(&id){
+                        return;
+                    }
+                    let sequence_number = sequence_numbers.entry((message.component_id, message.path.clone())).or_default();
+                    *sequence_number += 1;
+                    let data = message.open_config_data(round, *sequence_number, clock.as_millis() as u64);
+                    clock += Duration::from_millis(script.interval);
+                    if tx.send(Ok(data)).await.is_err(){
+                        return;
+                    }
+                    tokio::time::sleep(Duration::from_millis(script.interval)).await;
+                }
+                if !script.repeat || messages.is_empty(){
+                    break;
+                }
+                round += 1;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn cancel_telemetry_subscription(&self, request: Request<CancelSubscriptionRequest>) -> Result<Response<CancelSubscriptionReply>, Status>{
+        let id = request.into_inner().subscription_id;
+        info!("Cancel subscription {}", id);
+        let reply = if self.subscriptions.lock().unwrap().replies.remove(&id).is_some(){
+            CancelSubscriptionReply{
+                code: ReturnCode::Success.into(),
+                code_str: String::new(),
+            }
+        } else {
+            CancelSubscriptionReply{
+                code: ReturnCode::NoSubscriptionEntry.into(),
+                code_str: format!("no subscription {}", id),
+            }
+        };
+        Ok(Response::new(reply))
+    }
+
+    // all subscriptions for u32::MAX
+    async fn get_telemetry_subscriptions(&self, request: Request<GetSubscriptionsRequest>) -> Result<Response<GetSubscriptionsReply>, Status>{
+        let id = request.into_inner().subscription_id;
+        let subscriptions = self.subscriptions.lock().unwrap();
+        let mut subscription_list: Vec<SubscriptionReply> = subscriptions.replies.iter()
+            .filter(|(i, _)| id == u32::MAX || **i == id)
+            .map(|(_, reply)| reply.clone())
+            .collect();
+        subscription_list.sort_by_key(|reply| reply.response.as_ref().map(|r| r.subscription_id));
+        Ok(Response::new(GetSubscriptionsReply{
+            subscription_list,
+        }))
+    }
+
+    async fn get_telemetry_operational_state(&self, _request: Request<GetOperationalStateRequest>) -> Result<Response<GetOperationalStateReply>, Status>{
+        let kv = vec![
+            KeyValue{
+                key: "subscription_id".to_string(),
+                value: Some(key_value::Value::StrValue("1".to_string())),
+            },
+            KeyValue{
+                key: "packets_sent".to_string(),
+                value: Some(key_value::Value::UintValue(self.script.messages.len() as u64)),
+            },
+        ];
+        Ok(Response::new(GetOperationalStateReply{
+            kv,
+        }))
+    }
+
+    async fn get_data_encodings(&self, _request: Request<DataEncodingRequest>) -> Result<Response<DataEncodingReply>, Status>{
+        Ok(Response::new(DataEncodingReply{
+            encoding_list: vec![EncodingType::Proto3.into()],
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl GNmi for MockServer{
+    type SubscribeStream = ReceiverStream<Result<SubscribeResponse, Status>>;
+
+    async fn capabilities(&self, request: Request<CapabilityRequest>) -> Result<Response<CapabilityResponse>, Status>{
+        self.check_metadata(&request).map_err(|e| *e)?;
+        Ok(Response::new(CapabilityResponse{
+            supported_models: vec![ModelData{
+                name: "openconfig-interfaces".to_string(),
+                organization: "OpenConfig working group".to_string(),
+                version: "2.4.3".to_string(),
+            }],
+            supported_encodings: vec![Encoding::Json.into(), Encoding::JsonIetf.into(), Encoding::Proto.into()],
+            g_nmi_version: "0.7.0".to_string(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get(&self, _request: Request<GetRequest>) -> Result<Response<GetResponse>, Status>{
+        Err(Status::unimplemented("get is not supported by the mock server"))
+    }
+
+    async fn set(&self, _request: Request<SetRequest>) -> Result<Response<SetResponse>, Status>{
+        Err(Status::unimplemented("set is not supported by the mock server"))
+    }
+
+    async fn subscribe(&self, request: Request<Streaming<SubscribeRequest>>) -> Result<Response<Self::SubscribeStream>, Status>{
+        self.check_metadata(&request).map_err(|e| *e)?;
+        let mut requests = request.into_inner();
+        // the first request holds the subscription list
+        requests.message().await?;
+        let script = self.script.clone();
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move{
+            let mut round = 0;
+            let mut clock = now();
+            loop{
+                for notification in &script.notifications{
+                    let response = SubscribeResponse{
+                        response: Some(subscribe_response::Response::Update(notification.notification(round, clock.as_nanos() as i64))),
+                        ..Default::default()
+                    };
+                    clock += Duration::from_millis(script.interval);
+                    if tx.send(Ok(response)).await.is_err(){
+                        return;
+                    }
+                    tokio::time::sleep(Duration::from_millis(script.interval)).await;
+                }
+                if round == 0{
+                    let response = SubscribeResponse{
+                        response: Some(subscribe_response::Response::SyncResponse(true)),
+                        ..Default::default()
+                    };
+                    if tx.send(Ok(response)).await.is_err(){
+                        return;
+                    }
+                }
+                if !script.repeat || script.notifications.is_empty(){
+                    break;
+                }
+                round += 1;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
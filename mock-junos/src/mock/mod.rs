@@ -0,0 +1 @@
+pub mod mock;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod script;
\ No newline at end of file
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use crate::gnmi::gnmi::{typed_value, Notification, Path as GnmiPath, PathElem, TypedValue, Update};
+use crate::telemetry::telemetry::{key_value, KeyValue, OpenConfigData};
+
+// What the mock device streams to its subscribers:
+//   user: USER
+//   password: PWD
+//   interval: 1000
+//   repeat: true
+//   messages:
+//   - system_id: qfx1
+//     path: "sensor_1000:/interfaces/:/interfaces/:PFE"
+//     kv:
+//     - key: __prefix__
+//       str: "/interfaces/interface[name='et-0/0/0']/"
+//     - key: state/counters/in-octets
+//       uint: 1000
+//       increment: 100
+//   notifications:
+//   - prefix: /interfaces/interface[name=et-0/0/0]/state
+//     update:
+//     - path: counters/in-octets
+//       uint: 1000
+// Messages are sent to OpenConfigTelemetry subscribers which subscribed to
+// the subscribed path of the message, e.g. /interfaces/, notifications to
+// gNMI subscribers. Timestamps advance by the interval with every message.
+#[derive(serde::Deserialize, Clone, Default)]
+pub struct Script{
+    // credentials accepted by Login, any are accepted if not set
+    pub user: Option<String>,
+    pub password: Option<String>,
+    // pause between two messages in milliseconds
+    #[serde(default)]
+    pub interval: u64,
+    // start over with the first message after the last one
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub notifications: Vec<ScriptNotification>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct Message{
+    pub system_id: String,
+    #[serde(default)]
+    pub component_id: u32,
+    pub path: String,
+    #[serde(default)]
+    pub kv: Vec<Kv>,
+    #[serde(default)]
+    pub eom: bool,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ScriptNotification{
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub update: Vec<Kv>,
+}
+
+// a key or path with one value, uint values grow by increment every round
+#[derive(serde::Deserialize, Clone)]
+pub struct Kv{
+    #[serde(alias = "path")]
+    pub key: String,
+    pub uint: Option<u64>,
+    pub int: Option<i64>,
+    pub double: Option<f64>,
+    pub bool: Option<bool>,
+    pub str: Option<String>,
+    #[serde(default)]
+    pub increment: u64,
+}
+
+impl Script{
+    pub fn read(path: &str) -> anyhow::Result<Script>{
+        let script = std::fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&script)?)
+    }
+
+    pub fn authenticate(&self, user: &str, password: &str) -> bool{
+        self.user.as_deref().is_none_or(|u| u == user) && self.password.as_deref().is_none_or(|p| p == password)
+    }
+}
+
+impl Message{
+    // the subscribed path of <sensor name>:<subscribed path>:<path>:<component>
+    pub fn subscribed_path(&self) -> &str{
+        self.path.split(':').nth(1).unwrap_or(&self.path)
+    }
+
+    pub fn open_config_data(&self, round: u64, sequence_number: u64, timestamp: u64) -> OpenConfigData{
+        OpenConfigData{
+            system_id: self.system_id.clone(),
+            component_id: self.component_id,
+            path: self.path.clone(),
+            sequence_number,
+            timestamp,
+            kv: self.kv.iter().filter_map(|kv| {
+                let value = match kv.value(round)?{
+                    Value::Uint(v) => key_value::Value::UintValue(v),
+                    Value::Int(v) => key_value::Value::IntValue(v),
+                    Value::Double(v) => key_value::Value::DoubleValue(v),
+                    Value::Bool(v) => key_value::Value::BoolValue(v),
+                    Value::Str(v) => key_value::Value::StrValue(v),
+                };
+                Some(KeyValue{
+                    key: kv.key.clone(),
+                    value: Some(value),
+                })
+            }).collect(),
+            eom: if self.eom {
+                vec![crate::telemetry::telemetry::Eom{path: self.subscribed_path().to_string()}]
+            } else {
+                Vec::new()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl ScriptNotification{
+    pub fn notification(&self, round: u64, timestamp: i64) -> Notification{
+        Notification{
+            timestamp,
+            prefix: Some(parse_path(&self.prefix)),
+            update: self.update.iter().filter_map(|kv| {
+                let value = match kv.value(round)?{
+                    Value::Uint(v) => typed_value::Value::UintVal(v),
+                    Value::Int(v) => typed_value::Value::IntVal(v),
+                    Value::Double(v) => typed_value::Value::DoubleVal(v),
+                    Value::Bool(v) => typed_value::Value::BoolVal(v),
+                    Value::Str(v) => typed_value::Value::StringVal(v),
+                };
+                Some(Update{
+                    path: Some(parse_path(&kv.key)),
+                    val: Some(TypedValue{
+                        value: Some(value),
+                    }),
+                    ..Default::default()
+                })
+            }).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+enum Value{
+    Uint(u64),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Kv{
+    fn value(&self, round: u64) -> Option<Value>{
+        if let Some(v) = self.uint{
+            return Some(Value::Uint(v.wrapping_add(self.increment.wrapping_mul(round))));
+        }
+        self.int.map(Value::Int)
+            .or(self.double.map(Value::Double))
+            .or(self.bool.map(Value::Bool))
+            .or(self.str.clone().map(Value::Str))
+    }
+}
+
+// parses /interfaces/interface[name=et-0/0/0]/state into path elements
+fn parse_path(path: &str) -> GnmiPath{
+    let mut elem = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut elements = Vec::new();
+    for (idx, c) in path.char_indices(){
+        match c{
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '/' if depth == 0 => {
+                if idx > start{
+                    elements.push(&path[start..idx]);
+                }
+                start = idx + 1;
+            },
+            _ => {}
+        }
+    }
+    if path.len() > start{
+        elements.push(&path[start..]);
+    }
+    for element in elements{
+        let (name, keys) = match element.find('['){
+            Some(start) => (&element[..start], &element[start..]),
+            None => (element, ""),
+        };
+        let mut key = HashMap::new();
+        for predicate in keys.split(['[', ']']).filter(|p| !p.is_empty()){
+            if let Some((k, v)) = predicate.split_once('='){
+                key.insert(k.trim().to_string(), v.trim().trim_matches(['\'', '"']).to_string());
+            }
+        }
+        elem.push(PathElem{
+            name: name.to_string(),
+            key,
+        });
+    }
+    GnmiPath{
+        elem,
+        ..Default::default()
+    }
+}
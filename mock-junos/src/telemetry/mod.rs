@@ -0,0 +1,4 @@
+// This file is @generated by prost-build.
+pub mod telemetry {
+    include!("telemetry.rs");
+}
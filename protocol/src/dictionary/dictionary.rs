@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet};
+use crate::collector::collector::{CollectorMetrics, Sample, SeriesDefinition, SeriesMessage};
+use crate::metrics::metrics::series_id;
+
+// label sets defined on a stream before the definitions are reset
+const MAX_DEFINITIONS: usize = 100_000;
+
+// Sender side of a SendSeries stream, assigns ids to label sets and defines
+// every label set the first time it is sent. A dictionary belongs to one
+// stream, a new stream starts with a new dictionary.
+pub struct SeriesDictionary{
+    ids: HashMap<(Option<String>, String), u64>,
+    max_definitions: usize,
+}
+
+impl Default for SeriesDictionary{
+    fn default() -> Self{
+        SeriesDictionary::new(MAX_DEFINITIONS)
+    }
+}
+
+impl SeriesDictionary{
+    // the definitions are reset when a message would exceed
+    // max_definitions, a single message may still define more
+    pub fn new(max_definitions: usize) -> SeriesDictionary{
+        SeriesDictionary{
+            ids: HashMap::new(),
+            max_definitions,
+        }
+    }
+
+    pub fn encode(&mut self, metrics_list: Vec<CollectorMetrics>) -> SeriesMessage{
+        let mut message = SeriesMessage::default();
+        let keys: Vec<(Option<String>, String)> = metrics_list.iter()
+            .map(|metrics| (metrics.namespace.clone(), series_id("", &metrics.labels)))
+            .collect();
+        let new: HashSet<&(Option<String>, String)> = keys.iter().filter(|key| !self.ids.contains_key(*key)).collect();
+        if !self.ids.is_empty() && self.ids.len() + new.len() > self.max_definitions{
+            self.ids.clear();
+            message.reset = true;
+        }
+        for (metrics, key) in metrics_list.into_iter().zip(keys){
+            let next_id = self.ids.len() as u64;
+            let id = *self.ids.entry(key).or_insert_with(|| {
+                message.definitions.push(SeriesDefinition{
+                    id: next_id,
+                    namespace: metrics.namespace.clone(),
+                    labels: metrics.labels.clone(),
+                });
+                next_id
+            });
+            message.samples.push(Sample{
+                id,
+                metrics: metrics.metrics,
+            });
+        }
+        message
+    }
+}
+
+// Receiver side of a SendSeries stream
+#[derive(Default)]
+pub struct SeriesTable{
+    definitions: HashMap<u64, SeriesDefinition>,
+}
+
+impl SeriesTable{
+    // the samples of a message with the labels of their definitions, fails
+    // with the id of a sample whose label set was never defined
+    pub fn decode(&mut self, message: SeriesMessage) -> Result<Vec<CollectorMetrics>, u64>{
+        if message.reset{
+            self.definitions.clear();
+        }
+        for definition in message.definitions{
+            self.definitions.insert(definition.id, definition);
+        }
+        message.samples.into_iter().map(|sample| {
+            let definition = self.definitions.get(&sample.id).ok_or(sample.id)?;
+            Ok(CollectorMetrics{
+                namespace: definition.namespace.clone(),
+                labels: definition.labels.clone(),
+                metrics: sample.metrics,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port: &str, value: u64) -> CollectorMetrics{
+        CollectorMetrics::default()
+            .with_namespace("mlx")
+            .with_labels([("host", "node1"), ("device", "mlx5_0"), ("port", port)])
+            .with_metric("port_rcv_data", value)
+    }
+
+    #[test]
+    fn test_dictionary(){
+        let mut dictionary = SeriesDictionary::default();
+        let mut table = SeriesTable::default();
+
+        let first = dictionary.encode(vec![port("1", 1), port("2", 2)]);
+        assert_eq!(first.definitions.len(), 2);
+        assert_eq!(table.decode(first).unwrap(), vec![port("1", 1), port("2", 2)]);
+
+        // known label sets are only referenced
+        let second = dictionary.encode(vec![port("2", 3), port("1", 4), port("3", 5)]);
+        assert_eq!(second.definitions.len(), 1);
+        assert!(second.samples.iter().all(|s| s.metrics.len() == 1));
+        assert_eq!(table.decode(second).unwrap(), vec![port("2", 3), port("1", 4), port("3", 5)]);
+
+        // a new stream doesn't know the ids of the previous one
+        let third = dictionary.encode(vec![port("1", 6)]);
+        assert_eq!(SeriesTable::default().decode(third), Err(0));
+    }
+
+    #[test]
+    fn test_reset(){
+        let mut dictionary = SeriesDictionary::new(2);
+        let mut table = SeriesTable::default();
+
+        let first = dictionary.encode(vec![port("1", 1), port("2", 2)]);
+        assert!(!first.reset);
+        table.decode(first).unwrap();
+
+        // a third label set doesn't fit, all are defined again
+        let second = dictionary.encode(vec![port("3", 3), port("1", 4)]);
+        assert!(second.reset);
+        assert_eq!(second.definitions.len(), 2);
+        assert_eq!(table.decode(second).unwrap(), vec![port("3", 3), port("1", 4)]);
+        assert_eq!(table.definitions.len(), 2);
+        assert_eq!(dictionary.ids.len(), 2);
+
+        let third = dictionary.encode(vec![port("1", 5), port("3", 6)]);
+        assert!(!third.reset);
+        assert!(third.definitions.is_empty());
+        assert_eq!(table.decode(third).unwrap(), vec![port("1", 5), port("3", 6)]);
+    }
+}
@@ -0,0 +1 @@
+pub mod dictionary;
\ No newline at end of file
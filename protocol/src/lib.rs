@@ -0,0 +1,17 @@
+#![allow(clippy::module_inception)]
+// The collector protocol shared by the senders and the collector server.
+// Embedding a sender takes a few lines:
+//   let sender = Sender::new("127.0.0.1:50055".to_string());
+//   let client = sender.client();
+//   tokio::spawn(sender.run());
+//   let metrics = CollectorMetrics::default()
+//       .with_namespace("app")
+//       .with_label("host", "node1")
+//       .with_metric("requests", 0);
+//   client.register_metrics(metrics.clone()).await?;
+//   client.send(metrics.with_metric("requests", 42)).await?;
+pub mod collector;
+pub mod sender;
+pub mod metrics;
+pub mod dictionary;
+pub mod names;
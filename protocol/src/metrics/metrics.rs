@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use crate::collector::collector::{CollectorMetrics, MetricsBatch, Series};
+
+// builders for metrics sent to the collector, e.g.
+//   CollectorMetrics::default().with_namespace("mlx").with_label("port", "1").with_metric("port_rcv_data", 42)
+impl CollectorMetrics{
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self{
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self{
+        self.labels.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn with_labels<K: Into<String>, V: Into<String>>(mut self, labels: impl IntoIterator<Item = (K, V)>) -> Self{
+        self.labels.extend(labels.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    pub fn with_metric(mut self, name: impl Into<String>, value: u64) -> Self{
+        self.metrics.insert(name.into(), value);
+        self
+    }
+
+    // the same series with all values set to 0, as registered with the
+    // collector before the first values are sent
+    pub fn registration(&self) -> Self{
+        CollectorMetrics{
+            namespace: self.namespace.clone(),
+            labels: self.labels.clone(),
+            metrics: self.metrics.keys().map(|k| (k.clone(), 0)).collect(),
+        }
+    }
+
+    // series ids of all metrics
+    pub fn series_ids(&self) -> Vec<String>{
+        self.metrics.keys().map(|name| series_id(name, &self.labels)).collect()
+    }
+}
+
+impl MetricsBatch{
+    // the series with the labels of the batch added, labels of a series
+    // take precedence
+    pub fn into_metrics(self) -> Vec<CollectorMetrics>{
+        let MetricsBatch{ namespace, labels, series } = self;
+        series.into_iter().map(|s| {
+            let mut series_labels = labels.clone();
+            series_labels.extend(s.labels);
+            CollectorMetrics{
+                namespace: namespace.clone(),
+                labels: series_labels,
+                metrics: s.metrics,
+            }
+        }).collect()
+    }
+}
+
+// one batch per namespace, labels with the same value in all series of a
+// namespace are moved to the batch. The order of the series of a namespace
+// is kept.
+pub fn batches(metrics: Vec<CollectorMetrics>) -> Vec<MetricsBatch>{
+    let mut groups: Vec<(Option<String>, Vec<CollectorMetrics>)> = Vec::new();
+    for m in metrics{
+        match groups.iter_mut().find(|(namespace, _)| *namespace == m.namespace){
+            Some((_, group)) => group.push(m),
+            None => groups.push((m.namespace.clone(), vec![m])),
+        }
+    }
+    groups.into_iter().map(|(namespace, group)| {
+        let mut common = group[0].labels.clone();
+        for m in &group[1..]{
+            common.retain(|k, v| m.labels.get(k) == Some(v));
+        }
+        let series = group.into_iter().map(|m| Series{
+            labels: m.labels.into_iter().filter(|(k, _)| !common.contains_key(k)).collect(),
+            metrics: m.metrics,
+        }).collect();
+        MetricsBatch{
+            namespace,
+            labels: common,
+            series,
+        }
+    }).collect()
+}
+
+// canonical identity of a series: the metric name followed by the labels
+// sorted by name, so it does not depend on the order labels were collected in
+pub fn series_id(metric_name: &str, labels: &HashMap<String, String>) -> String{
+    let mut labels: Vec<(&String, &String)> = labels.iter().collect();
+    labels.sort();
+    let labels: Vec<String> = labels.into_iter().map(|(k, v)| format!("{}={:?}", k, v)).collect();
+    format!("{}{{{}}}", metric_name, labels.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_id(){
+        let a: HashMap<String, String> = [("b", "2"), ("a", "1"), ("c", "3")].iter()
+            .map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let b: HashMap<String, String> = [("c", "3"), ("a", "1"), ("b", "2")].iter()
+            .map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(series_id("m", &a), series_id("m", &b));
+        assert_eq!(series_id("m", &a), r#"m{a="1",b="2",c="3"}"#);
+        assert_ne!(series_id("m", &a), series_id("n", &a));
+        // same values under different label names are different series
+        let c: HashMap<String, String> = [("a", "2"), ("b", "1"), ("c", "3")].iter()
+            .map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_ne!(series_id("m", &a), series_id("m", &c));
+    }
+
+    #[test]
+    fn test_builder(){
+        let metrics = CollectorMetrics::default()
+            .with_namespace("mlx")
+            .with_labels([("device", "mlx5_0"), ("port", "1")])
+            .with_label("host", "node1")
+            .with_metric("port_rcv_data", 42);
+        assert_eq!(metrics.namespace.as_deref(), Some("mlx"));
+        assert_eq!(metrics.labels.len(), 3);
+        assert_eq!(metrics.registration().metrics.get("port_rcv_data"), Some(&0));
+        assert_eq!(metrics.series_ids(), [r#"port_rcv_data{device="mlx5_0",host="node1",port="1"}"#]);
+    }
+
+    #[test]
+    fn test_batches(){
+        let port = |ns: &str, port: &str, value: u64| CollectorMetrics::default()
+            .with_namespace(ns)
+            .with_labels([("host", "node1"), ("device", "mlx5_0"), ("port", port)])
+            .with_metric("port_rcv_data", value);
+        let metrics = vec![port("mlx", "1", 1), port("qfx", "1", 2), port("mlx", "2", 3), port("mlx", "1", 4)];
+        let batches = batches(metrics.clone());
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].namespace.as_deref(), Some("mlx"));
+        assert_eq!(batches[0].labels.len(), 2);
+        assert!(!batches[0].labels.contains_key("port"));
+        assert!(batches[0].series.iter().all(|s| s.labels.len() == 1));
+        // a single series keeps all its labels in the batch
+        assert_eq!(batches[1].labels.len(), 3);
+        assert!(batches[1].series[0].labels.is_empty());
+
+        let expanded: Vec<CollectorMetrics> = batches.into_iter().flat_map(|b| b.into_metrics()).collect();
+        assert_eq!(expanded, vec![metrics[0].clone(), metrics[2].clone(), metrics[3].clone(), metrics[1].clone()]);
+    }
+}
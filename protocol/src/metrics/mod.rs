@@ -0,0 +1 @@
+pub mod metrics;
\ No newline at end of file
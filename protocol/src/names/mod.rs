@@ -0,0 +1 @@
+pub mod names;
\ No newline at end of file
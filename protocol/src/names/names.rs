@@ -0,0 +1,42 @@
+// The names the collector exports, shared by the senders and the collector
+// server so a name is valid before it is sent. Metric names may only contain
+// letters, digits, _ and :, label names no :, neither starts with a digit.
+// Other characters (e.g. the - of in-octets) become _, a leading digit gets
+// a _ prefix.
+pub fn metric_name(name: &str) -> String{
+    valid_name(name, |c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+pub fn label_name(name: &str) -> String{
+    valid_name(name, |c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn valid_name(name: &str, valid: impl Fn(char) -> bool) -> String{
+    let mut name: String = name.chars()
+        .map(|c| if valid(c) { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()){
+        name.insert(0, '_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_name(){
+        assert_eq!(metric_name("in-octets"), "in_octets");
+        assert_eq!(metric_name("junos:in_octets"), "junos:in_octets");
+        assert_eq!(metric_name("5min-load"), "_5min_load");
+        assert_eq!(metric_name("et-0/0/0.rx"), "et_0_0_0_rx");
+    }
+
+    #[test]
+    fn test_label_name(){
+        assert_eq!(label_name("interface-name"), "interface_name");
+        assert_eq!(label_name("oc:name"), "oc_name");
+        assert_eq!(label_name("0index"), "_0index");
+    }
+}
@@ -0,0 +1,790 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use log::{error, info, warn};
+use prost::Message;
+use tokio::sync::mpsc::{self, Receiver};
+use tokio::time::{interval, sleep, Duration, MissedTickBehavior};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status, Streaming};
+use crate::collector::collector::{collector_server_client::CollectorServerClient, Ack, CollectorMetrics, MetricsBatch, Reply, SequencedBatch};
+use crate::dictionary::dictionary::SeriesDictionary;
+use crate::metrics::metrics::batches;
+
+// metrics queued while the collector is slow or unreachable
+const CHANNEL_SIZE: usize = 10000;
+// delay before reconnecting, doubled after every failed attempt
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+// a batch is sent once it has this many series or encoded bytes, or when
+// its first series waited for the batch delay
+const BATCH_SERIES: usize = 1000;
+const BATCH_BYTES: usize = 1024 * 1024;
+const BATCH_DELAY: Duration = Duration::from_millis(100);
+// the first metrics sent on a stream are kept so they can be sent again if
+// the collector rejects the stream as unimplemented, which it does right away
+const RETAIN_SERIES: usize = 1000;
+// batches sent on an acked stream and not acknowledged yet, no more metrics
+// are read while this many are waiting
+const MAX_UNACKED: usize = 16;
+
+// Streams the metrics sent by its clients to the collector server in
+// batches. A lost connection is re-established with backoff, metrics queued
+// meanwhile and metrics which weren't handed to the failed stream yet are sent
+// on the new stream. Metrics already handed to a stream which fails may be
+// lost, unless the protocol is Acked. run returns once all clients are
+// dropped and the queued metrics are sent.
+pub struct Sender{
+    address: String,
+    rx: Receiver<CollectorMetrics>,
+    client: Client,
+    limits: BatchLimits,
+    compression: Compression,
+    protocol: Protocol,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression{
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression{
+    fn encoding(&self) -> Option<CompressionEncoding>{
+        match self{
+            Compression::None => None,
+            Compression::Gzip => Some(CompressionEncoding::Gzip),
+            Compression::Zstd => Some(CompressionEncoding::Zstd),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol{
+    // SendMetrics, one series per message, served by every collector
+    Metrics,
+    // SendBatches, every batch carries the labels of its series
+    #[default]
+    Batches,
+    // SendSeries, label sets are sent once per stream and referenced by id
+    SeriesDictionary,
+    // SendAcked, batches are sent again on the next stream until the
+    // collector acknowledged them. There's no fallback, the sender fails if
+    // the collector doesn't serve it
+    Acked,
+}
+
+impl Protocol{
+    // the protocol tried next when the collector doesn't serve this one
+    fn fallback(&self) -> Option<Protocol>{
+        match self{
+            Protocol::Metrics | Protocol::Acked => None,
+            Protocol::Batches => Some(Protocol::Metrics),
+            Protocol::SeriesDictionary => Some(Protocol::Batches),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BatchLimits{
+    series: usize,
+    bytes: usize,
+    delay: Duration,
+}
+
+impl Sender{
+    pub fn new(address: String) -> Sender{
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+        Sender{
+            address: address.clone(),
+            rx,
+            client: Client::new(tx, address),
+            limits: BatchLimits{
+                series: BATCH_SERIES,
+                bytes: BATCH_BYTES,
+                delay: BATCH_DELAY,
+            },
+            compression: Compression::None,
+            protocol: Protocol::Batches,
+        }
+    }
+
+    // compresses the stream, the sender falls back to no compression if the
+    // collector answers a compressed call as unimplemented
+    pub fn with_compression(mut self, compression: Compression) -> Sender{
+        self.compression = compression;
+        self
+    }
+
+    // the sender falls back to older protocols the collector supports, see
+    // Protocol::fallback
+    pub fn with_protocol(mut self, protocol: Protocol) -> Sender{
+        self.protocol = protocol;
+        self
+    }
+
+    // batches hold up to series entries and are sent when full or when
+    // their first entry waited for delay, 1 disables batching
+    pub fn with_batch_limits(mut self, series: usize, delay: Duration) -> Sender{
+        self.limits.series = series.max(1);
+        self.limits.delay = delay;
+        self
+    }
+
+    pub fn client(&self) -> Client{
+        self.client.clone()
+    }
+
+    pub async fn run(self) -> anyhow::Result<()>{
+        let Sender{ address, mut rx, client, limits, mut compression, mut protocol } = self;
+        drop(client);
+        let mut backoff = MIN_BACKOFF;
+        let mut requeued = Vec::new();
+        loop{
+            match CollectorServerClient::connect(format!("http://{}", address)).await{
+                Ok(mut collector_client) => {
+                    info!("Connected to collector {}", address);
+                    backoff = MIN_BACKOFF;
+                    if let Some(encoding) = compression.encoding(){
+                        collector_client = collector_client.send_compressed(encoding);
+                    }
+                    collector_client = collector_client
+                        .accept_compressed(CompressionEncoding::Gzip)
+                        .accept_compressed(CompressionEncoding::Zstd);
+                    let failure = match stream(collector_client, &mut rx, limits, protocol, std::mem::take(&mut requeued)).await{
+                        Ok(()) => {
+                            info!("All metrics sent to collector {}", address);
+                            return Ok(());
+                        },
+                        Err(failure) => failure,
+                    };
+                    let Failure{ status, sent, unsent } = failure;
+                    requeued = unsent;
+                    // older collectors reject unknown compressions and calls
+                    // without accepting any metrics. A compressed call is
+                    // tried uncompressed first, then the older protocols
+                    if status.is_some_and(|s| s.code() == Code::Unimplemented){
+                        if compression != Compression::None{
+                            warn!("Collector {} does not serve {:?} with {:?} compression, sending uncompressed", address, protocol, compression);
+                            compression = Compression::None;
+                            requeued.splice(0..0, sent);
+                            continue;
+                        }
+                        let Some(fallback) = protocol.fallback() else {
+                            anyhow::bail!("Collector {} does not serve the {:?} protocol", address, protocol);
+                        };
+                        warn!("Collector {} does not support the {:?} protocol, sending {:?}", address, protocol, fallback);
+                        protocol = fallback;
+                        requeued.splice(0..0, sent);
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    warn!("Failed to connect to collector {}: {}", address, e);
+                },
+            }
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+// metrics waiting to be sent
+struct Pending{
+    metrics: Vec<CollectorMetrics>,
+    bytes: usize,
+}
+
+impl Pending{
+    fn push(&mut self, metrics: CollectorMetrics){
+        self.bytes += metrics.encoded_len();
+        self.metrics.push(metrics);
+    }
+
+    fn full(&self, limits: &BatchLimits) -> bool{
+        self.metrics.len() >= limits.series || self.bytes >= limits.bytes
+    }
+
+    fn take(&mut self) -> Vec<CollectorMetrics>{
+        self.bytes = 0;
+        std::mem::take(&mut self.metrics)
+    }
+}
+
+// why a stream ended before all clients were dropped
+struct Failure{
+    // the status returned by the collector, if any
+    status: Option<Status>,
+    // the first metrics sent on the stream, see RETAIN_SERIES
+    sent: Vec<CollectorMetrics>,
+    // the metrics taken from the queue but not handed to the stream, on an
+    // acked stream also the ones which weren't acknowledged
+    unsent: Vec<CollectorMetrics>,
+}
+
+// streams the requeued metrics and then the metrics of the clients with the
+// protocol until the stream fails or all clients are dropped
+async fn stream(mut collector_client: CollectorServerClient<Channel>, rx: &mut Receiver<CollectorMetrics>, limits: BatchLimits, protocol: Protocol, requeued: Vec<CollectorMetrics>) -> Result<(), Failure>{
+    match protocol{
+        Protocol::Metrics => {
+            let (tx, stream_rx) = mpsc::channel(1);
+            let call = collector_client.send_metrics(Request::new(ReceiverStream::new(stream_rx)));
+            forward(call, tx, rx, limits, requeued, |metrics_list| metrics_list.to_vec()).await
+        },
+        Protocol::Batches => {
+            let (tx, stream_rx) = mpsc::channel(1);
+            let call = collector_client.send_batches(Request::new(ReceiverStream::new(stream_rx)));
+            forward(call, tx, rx, limits, requeued, |metrics_list| batches(metrics_list.to_vec())).await
+        },
+        Protocol::SeriesDictionary => {
+            let (tx, stream_rx) = mpsc::channel(1);
+            let call = collector_client.send_series(Request::new(ReceiverStream::new(stream_rx)));
+            let mut dictionary = SeriesDictionary::default();
+            forward(call, tx, rx, limits, requeued, |metrics_list| vec![dictionary.encode(metrics_list.to_vec())]).await
+        },
+        Protocol::Acked => forward_acked(collector_client, rx, limits, requeued).await,
+    }
+}
+
+// collects the metrics and passes them to the call as encoded by encode
+async fn forward<M, F, E>(call: F, tx: mpsc::Sender<M>, rx: &mut Receiver<CollectorMetrics>, limits: BatchLimits, requeued: Vec<CollectorMetrics>, mut encode: E) -> Result<(), Failure>
+where
+    F: Future<Output = Result<Response<Reply>, Status>>,
+    E: FnMut(&[CollectorMetrics]) -> Vec<M>,
+{
+    tokio::pin!(call);
+    let mut pending = Pending{ metrics: Vec::new(), bytes: 0 };
+    // sent with the first tick
+    for metrics in requeued{
+        pending.push(metrics);
+    }
+    let mut sent = Vec::new();
+    let mut flush = interval(limits.delay);
+    flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop{
+        let metrics_list = tokio::select! {
+            res = &mut call => {
+                match &res{
+                    Ok(_) => warn!("Collector closed the metrics stream"),
+                    Err(e) => error!("Metrics stream failed: {}", e),
+                }
+                return Err(Failure{ status: res.err(), sent, unsent: pending.take() });
+            },
+            metrics = rx.recv() => {
+                let Some(metrics) = metrics else {
+                    let metrics_list = pending.take();
+                    if !metrics_list.is_empty(){
+                        if let Err(status) = hand_over(call.as_mut(), &tx, encode(&metrics_list)).await{
+                            return Err(Failure{ status, sent, unsent: metrics_list });
+                        }
+                        retain(&mut sent, metrics_list);
+                    }
+                    drop(tx);
+                    return match call.await{
+                        Ok(_) => Ok(()),
+                        Err(e) => {
+                            error!("Metrics stream failed: {}", e);
+                            Err(Failure{ status: Some(e), sent, unsent: Vec::new() })
+                        },
+                    };
+                };
+                if pending.metrics.is_empty(){
+                    flush.reset();
+                }
+                pending.push(metrics);
+                if !pending.full(&limits){
+                    continue;
+                }
+                pending.take()
+            },
+            _ = flush.tick() => pending.take(),
+        };
+        if metrics_list.is_empty(){
+            continue;
+        }
+        // the messages handed to a failed call may be lost, the list is
+        // sent again
+        if let Err(status) = hand_over(call.as_mut(), &tx, encode(&metrics_list)).await{
+            return Err(Failure{ status, sent, unsent: metrics_list });
+        }
+        retain(&mut sent, metrics_list);
+    }
+}
+
+// hands the messages to the call, which is polled meanwhile so the request
+// is sent. Fails with the status of the call if the collector ended it.
+async fn hand_over<M, F>(mut call: Pin<&mut F>, tx: &mpsc::Sender<M>, messages: Vec<M>) -> Result<(), Option<Status>>
+where
+    F: Future<Output = Result<Response<Reply>, Status>>,
+{
+    for message in messages{
+        tokio::select! {
+            res = tx.send(message) => {
+                if res.is_err(){
+                    return Err(call.await.err());
+                }
+            },
+            res = &mut call => return Err(res.err()),
+        }
+    }
+    Ok(())
+}
+
+fn retain(sent: &mut Vec<CollectorMetrics>, metrics_list: Vec<CollectorMetrics>){
+    if sent.len() < RETAIN_SERIES{
+        sent.extend(metrics_list);
+    }
+}
+
+// batches sent on an acked stream waiting for their ack
+#[derive(Default)]
+struct Unacked{
+    next_sequence: u64,
+    batches: BTreeMap<u64, MetricsBatch>,
+}
+
+impl Unacked{
+    fn push(&mut self, batch: MetricsBatch) -> SequencedBatch{
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.batches.insert(sequence, batch.clone());
+        SequencedBatch{ sequence, batch: Some(batch) }
+    }
+
+    fn ack(&mut self, ack: Ack){
+        // a rejected batch would be rejected again
+        if self.batches.remove(&ack.sequence).is_some() && !ack.error.is_empty(){
+            error!("Collector rejected metrics: {}", ack.error);
+        }
+    }
+
+    // the unacknowledged metrics in the order they were sent, followed by
+    // the pending ones
+    fn failure(self, status: Option<Status>, mut pending: Pending) -> Failure{
+        let mut unsent: Vec<CollectorMetrics> = self.batches.into_values().flat_map(|b| b.into_metrics()).collect();
+        unsent.extend(pending.take());
+        Failure{ status, sent: Vec::new(), unsent }
+    }
+}
+
+// like forward with every batch kept until the collector acknowledged it,
+// when all clients are dropped the acks of the last batches are awaited
+async fn forward_acked(mut collector_client: CollectorServerClient<Channel>, rx: &mut Receiver<CollectorMetrics>, limits: BatchLimits, requeued: Vec<CollectorMetrics>) -> Result<(), Failure>{
+    let mut pending = Pending{ metrics: Vec::new(), bytes: 0 };
+    let mut unacked = Unacked::default();
+    let (tx, stream_rx) = mpsc::channel(1);
+    let mut acks = match collector_client.send_acked(Request::new(ReceiverStream::new(stream_rx))).await{
+        Ok(response) => response.into_inner(),
+        Err(e) => {
+            error!("Metrics stream failed: {}", e);
+            return Err(unacked.failure(Some(e), pending));
+        },
+    };
+    // dropped once all clients are gone
+    let mut tx = Some(tx);
+    for metrics_list in requeued.chunks(limits.series){
+        if let Err(status) = send_acked(&mut tx, &mut acks, &mut unacked, metrics_list.to_vec()).await{
+            return Err(unacked.failure(status, pending));
+        }
+    }
+    let mut flush = interval(limits.delay);
+    flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop{
+        let metrics_list = tokio::select! {
+            ack = acks.message() => {
+                match ack{
+                    Ok(Some(ack)) => unacked.ack(ack),
+                    Ok(None) => {
+                        warn!("Collector closed the metrics stream");
+                        return Err(unacked.failure(None, pending));
+                    },
+                    Err(e) => {
+                        error!("Metrics stream failed: {}", e);
+                        return Err(unacked.failure(Some(e), pending));
+                    },
+                }
+                if tx.is_none() && unacked.batches.is_empty(){
+                    return Ok(());
+                }
+                continue;
+            },
+            metrics = rx.recv(), if tx.is_some() && unacked.batches.len() < MAX_UNACKED => {
+                let Some(metrics) = metrics else {
+                    let metrics_list = pending.take();
+                    if let Err(status) = send_acked(&mut tx, &mut acks, &mut unacked, metrics_list).await{
+                        return Err(unacked.failure(status, pending));
+                    }
+                    tx = None;
+                    if unacked.batches.is_empty(){
+                        return Ok(());
+                    }
+                    continue;
+                };
+                if pending.metrics.is_empty(){
+                    flush.reset();
+                }
+                pending.push(metrics);
+                if !pending.full(&limits){
+                    continue;
+                }
+                pending.take()
+            },
+            _ = flush.tick(), if tx.is_some() => pending.take(),
+        };
+        if let Err(status) = send_acked(&mut tx, &mut acks, &mut unacked, metrics_list).await{
+            return Err(unacked.failure(status, pending));
+        }
+    }
+}
+
+// sends the metrics in batches, fails with the status of the call if the
+// collector ended it
+async fn send_acked(tx: &mut Option<mpsc::Sender<SequencedBatch>>, acks: &mut Streaming<Ack>, unacked: &mut Unacked, metrics_list: Vec<CollectorMetrics>) -> Result<(), Option<Status>>{
+    let Some(tx) = tx else {
+        return Ok(());
+    };
+    for batch in batches(metrics_list){
+        if tx.send(unacked.push(batch)).await.is_err(){
+            // the acks received before the call ended still count
+            loop{
+                match acks.message().await{
+                    Ok(Some(ack)) => unacked.ack(ack),
+                    Ok(None) => return Err(None),
+                    Err(e) => return Err(Some(e)),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Client{
+    tx: mpsc::Sender<CollectorMetrics>,
+    address: String,
+}
+
+impl Client{
+    pub fn new(tx: mpsc::Sender<CollectorMetrics>, address: String) -> Client{
+        Client{
+            tx,
+            address,
+        }
+    }
+
+    pub async fn send(&self, metrics: CollectorMetrics) -> anyhow::Result<()>{
+        self.tx.send(metrics).await?;
+        Ok(())
+    }
+
+    pub async fn register_metrics(&self, metrics: CollectorMetrics) -> anyhow::Result<()>{
+        info!("Registering {} metrics", metrics.metrics.len());
+        let mut collector_client = CollectorServerClient::connect(format!("http://{}", self.address)).await?;
+        collector_client.register_metrics(Request::new(metrics)).await?;
+        Ok(())
+    }
+
+    pub async fn unregister_metrics(&self, metrics: CollectorMetrics) -> anyhow::Result<()>{
+        info!("Unregistering {} metrics", metrics.metrics.len());
+        let mut collector_client = CollectorServerClient::connect(format!("http://{}", self.address)).await?;
+        collector_client.unregister_metrics(Request::new(metrics)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::{watch, Notify};
+    use tokio::task::JoinHandle;
+    use tokio_stream::StreamExt;
+    use tonic::{transport::Server, Response, Status, Streaming};
+    use crate::collector::collector::{collector_server_server::{CollectorServer, CollectorServerServer}, MetricsBatch, SeriesMessage};
+    use crate::dictionary::dictionary::SeriesTable;
+
+    // the calls a test collector serves
+    #[derive(Clone, Copy, PartialEq, PartialOrd)]
+    enum Version{
+        // SendMetrics only, like collectors from before batches
+        Metrics,
+        // SendBatches without compression
+        Batches,
+        // everything, like the collector server
+        Current,
+    }
+
+    // passes the received metrics on to the test with the call they were
+    // received with
+    struct TestServer{
+        tx: mpsc::Sender<(&'static str, Vec<CollectorMetrics>)>,
+        version: Version,
+        // whether an acked stream failed before acknowledging a batch
+        failed: Arc<AtomicBool>,
+        // notified when a SendBatches stream starts
+        opened: Arc<Notify>,
+        // ends the SendBatches streams, a graceful shutdown waits for them
+        stop: watch::Receiver<bool>,
+    }
+
+    #[tonic::async_trait]
+    impl CollectorServer for TestServer{
+        async fn send_metrics(&self, request: Request<Streaming<CollectorMetrics>>) -> Result<Response<Reply>, Status>{
+            let mut stream = request.into_inner();
+            while let Some(metrics) = stream.next().await{
+                let _ = self.tx.send(("metrics", vec![metrics?])).await;
+            }
+            Ok(Response::new(Reply::default()))
+        }
+        async fn send_batches(&self, request: Request<Streaming<MetricsBatch>>) -> Result<Response<Reply>, Status>{
+            if self.version < Version::Batches{
+                return Err(Status::unimplemented(""));
+            }
+            self.opened.notify_one();
+            let mut stream = request.into_inner();
+            let mut stop = self.stop.clone();
+            loop{
+                tokio::select! {
+                    batch = stream.next() => match batch{
+                        Some(batch) => { let _ = self.tx.send(("batches", batch?.into_metrics())).await; },
+                        None => break,
+                    },
+                    _ = stop.changed() => return Err(Status::unavailable("stopped")),
+                }
+            }
+            Ok(Response::new(Reply::default()))
+        }
+        type SendAckedStream = ReceiverStream<Result<Ack, Status>>;
+        // the first stream fails without acknowledging its first batch,
+        // series labelled reject are rejected
+        async fn send_acked(&self, request: Request<Streaming<SequencedBatch>>) -> Result<Response<Self::SendAckedStream>, Status>{
+            if self.version < Version::Current{
+                return Err(Status::unimplemented(""));
+            }
+            let mut stream = request.into_inner();
+            let (ack_tx, ack_rx) = mpsc::channel(100);
+            let tx = self.tx.clone();
+            let failed = self.failed.clone();
+            tokio::spawn(async move{
+                while let Some(Ok(sequenced)) = stream.next().await{
+                    let metrics_list = sequenced.batch.unwrap_or_default().into_metrics();
+                    let rejected = metrics_list.iter().any(|m| m.labels.contains_key("reject"));
+                    let _ = tx.send(("acked", metrics_list)).await;
+                    if !failed.swap(true, Ordering::SeqCst){
+                        let _ = ack_tx.send(Err(Status::unavailable("failed"))).await;
+                        return;
+                    }
+                    let error = if rejected { "rejected".to_string() } else { String::new() };
+                    let _ = ack_tx.send(Ok(Ack{ sequence: sequenced.sequence, error })).await;
+                }
+            });
+            Ok(Response::new(ReceiverStream::new(ack_rx)))
+        }
+        async fn send_series(&self, request: Request<Streaming<SeriesMessage>>) -> Result<Response<Reply>, Status>{
+            if self.version < Version::Current{
+                return Err(Status::unimplemented(""));
+            }
+            let mut stream = request.into_inner();
+            let mut table = SeriesTable::default();
+            while let Some(message) = stream.next().await{
+                let metrics_list = table.decode(message?).map_err(|id| Status::invalid_argument(format!("unknown series {}", id)))?;
+                let _ = self.tx.send(("series", metrics_list)).await;
+            }
+            Ok(Response::new(Reply::default()))
+        }
+        async fn register_metrics(&self, _request: Request<CollectorMetrics>) -> Result<Response<Reply>, Status>{
+            Ok(Response::new(Reply::default()))
+        }
+        async fn unregister_metrics(&self, _request: Request<CollectorMetrics>) -> Result<Response<Reply>, Status>{
+            Ok(Response::new(Reply::default()))
+        }
+    }
+
+    // stopped when dropped
+    struct TestCollector{
+        stop: watch::Sender<bool>,
+        opened: Arc<Notify>,
+        handle: JoinHandle<()>,
+    }
+
+    impl TestCollector{
+        // returns once the address is free again
+        async fn stop(self){
+            let _ = self.stop.send(true);
+            self.handle.await.unwrap();
+        }
+    }
+
+    fn start_server(address: &str, tx: mpsc::Sender<(&'static str, Vec<CollectorMetrics>)>, version: Version) -> TestCollector{
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        let address = address.parse().unwrap();
+        let opened = Arc::new(Notify::new());
+        let mut service = CollectorServerServer::new(TestServer{ tx, version, failed: Arc::default(), opened: opened.clone(), stop: stop_rx.clone() });
+        if version == Version::Current{
+            service = service
+                .accept_compressed(CompressionEncoding::Gzip)
+                .accept_compressed(CompressionEncoding::Zstd);
+        }
+        let handle = tokio::spawn(async move{
+            Server::builder()
+                .add_service(service)
+                .serve_with_shutdown(address, async move { let _ = stop_rx.changed().await; })
+                .await
+                .unwrap();
+        });
+        TestCollector{ stop: stop_tx, opened, handle }
+    }
+
+    fn metrics(value: u64) -> CollectorMetrics{
+        CollectorMetrics::default().with_label("host", "node1").with_metric("requests", value)
+    }
+
+    #[tokio::test]
+    async fn test_reconnect(){
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let sender = Sender::new(address.clone());
+        let client = sender.client();
+        let handle = tokio::spawn(sender.run());
+
+        // metrics sent before the collector is up are queued
+        client.send(metrics(1)).await.unwrap();
+        let collector = start_server(&address, tx.clone(), Version::Current);
+        assert_eq!(rx.recv().await.unwrap().1, [metrics(1)]);
+
+        collector.stop().await;
+        let collector = start_server(&address, tx, Version::Current);
+        // the sender noticed the closed stream once it streams to the new
+        // collector
+        collector.opened.notified().await;
+        client.send(metrics(2)).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().1, [metrics(2)]);
+
+        drop(client);
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batching(){
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let _stop = start_server(&address, tx, Version::Current);
+        let sender = Sender::new(address.clone()).with_batch_limits(3, Duration::from_millis(200));
+        let client = sender.client();
+        let handle = tokio::spawn(sender.run());
+
+        // full batches are sent right away
+        for value in 0..6{
+            client.send(metrics(value)).await.unwrap();
+        }
+        for values in [[0, 1, 2], [3, 4, 5]]{
+            let batch = tokio::time::timeout(Duration::from_millis(150), rx.recv()).await.unwrap().unwrap();
+            assert_eq!(batch.1, values.map(metrics));
+        }
+        // the rest after the delay
+        client.send(metrics(6)).await.unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(100), rx.recv()).await.is_err());
+        let batch = tokio::time::timeout(Duration::from_millis(300), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(batch.1, [metrics(6)]);
+
+        // and the pending series when the clients are gone
+        client.send(metrics(7)).await.unwrap();
+        drop(client);
+        handle.await.unwrap().unwrap();
+        assert_eq!(rx.recv().await.unwrap().1, [metrics(7)]);
+    }
+
+    async fn received(compression: Compression, protocol: Protocol, version: Version) -> Vec<(&'static str, Vec<CollectorMetrics>)>{
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let _stop = start_server(&address, tx, version);
+        let sender = Sender::new(address.clone())
+            .with_batch_limits(2, Duration::from_millis(100))
+            .with_compression(compression)
+            .with_protocol(protocol);
+        let client = sender.client();
+        let handle = tokio::spawn(sender.run());
+        for value in 0..4{
+            client.send(metrics(value)).await.unwrap();
+        }
+        drop(client);
+        handle.await.unwrap().unwrap();
+        let mut received = Vec::new();
+        while let Ok(r) = rx.try_recv(){
+            received.push(r);
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn test_compression_and_dictionary(){
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd]{
+            let received = received(compression, Protocol::SeriesDictionary, Version::Current).await;
+            assert_eq!(received, [("series", vec![metrics(0), metrics(1)]), ("series", vec![metrics(2), metrics(3)])], "{:?}", compression);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fallback(){
+        // the collector rejects the compression, then the series dictionary
+        let batches = received(Compression::Zstd, Protocol::SeriesDictionary, Version::Batches).await;
+        let metrics_list: Vec<CollectorMetrics> = batches.iter().flat_map(|(_, m)| m.clone()).collect();
+        assert!(batches.iter().all(|(call, _)| *call == "batches"));
+        assert_eq!(metrics_list, (0..4).map(metrics).collect::<Vec<_>>());
+
+        // and batches
+        let received = received(Compression::Zstd, Protocol::SeriesDictionary, Version::Metrics).await;
+        assert_eq!(received, (0..4).map(|value| ("metrics", vec![metrics(value)])).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_acked(){
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let _stop = start_server(&address, tx, Version::Current);
+        let sender = Sender::new(address.clone())
+            .with_batch_limits(2, Duration::from_millis(100))
+            .with_protocol(Protocol::Acked);
+        let client = sender.client();
+        let handle = tokio::spawn(sender.run());
+        let rejected = |value| metrics(value).with_label("reject", "");
+        for value in [metrics(0), metrics(1), rejected(2), rejected(3), metrics(4), metrics(5)]{
+            client.send(value).await.unwrap();
+        }
+        // the batch lost with the first stream is sent again, the rejected
+        // one is not
+        drop(client);
+        handle.await.unwrap().unwrap();
+        let mut batches = Vec::new();
+        while let Ok((call, metrics_list)) = rx.try_recv(){
+            assert_eq!(call, "acked");
+            batches.push(metrics_list);
+        }
+        assert_eq!(batches, [
+            vec![metrics(0), metrics(1)],
+            vec![metrics(0), metrics(1)],
+            vec![rejected(2), rejected(3)],
+            vec![metrics(4), metrics(5)],
+        ]);
+
+        // older collectors can't keep the guarantee
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (tx, mut rx) = mpsc::channel(100);
+        let _stop = start_server(&address, tx, Version::Batches);
+        let sender = Sender::new(address.clone())
+            .with_compression(Compression::Zstd)
+            .with_protocol(Protocol::Acked);
+        let client = sender.client();
+        let handle = tokio::spawn(sender.run());
+        client.send(metrics(0)).await.unwrap();
+        assert!(handle.await.unwrap().is_err());
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -1,4 +0,0 @@
-// This file is @generated by prost-build.
-pub mod collector {
-    include!("collector.rs");
-}
@@ -1,11 +1,16 @@
-use crate::collector::collector::{
+use collector_protocol::collector::collector::{
     collector_server_server::{CollectorServer, CollectorServerServer},
-    CollectorMetrics, Reply,
+    Ack, CollectorMetrics, MetricsBatch, Reply, SequencedBatch, SeriesMessage,
 };
+use collector_protocol::dictionary::dictionary::SeriesTable;
 use crate::prometheus::prometheus::Client as PrometheusClient;
-use log::info;
-use tonic::{transport::Server, Request, Response, Status, Streaming};
-use tokio_stream::StreamExt;
+use log::{error, info};
+use tonic::{codec::CompressionEncoding, transport::Server, Request, Response, Status, Streaming};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+
+// acks waiting to be sent on a SendAcked stream
+const ACKS: usize = 100;
 
 #[derive(Clone)]
 pub struct GrpcServer{
@@ -24,7 +29,11 @@ impl GrpcServer {
     pub async fn run(&self) -> anyhow::Result<()>{
         info!("Server listening on {}", self.address);
         Server::builder()
-            .add_service(CollectorServerServer::new(self.clone()))
+            .add_service(CollectorServerServer::new(self.clone())
+                .accept_compressed(CompressionEncoding::Gzip)
+                .accept_compressed(CompressionEncoding::Zstd)
+                .send_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Zstd))
             .serve(self.address.parse().unwrap())
             .await?;
         Ok(())
@@ -48,6 +57,70 @@ impl CollectorServer for GrpcServer {
         }
         Ok(Response::new(Reply::default()))
     }
+    async fn send_batches(
+        &self,
+        request: Request<Streaming<MetricsBatch>>,
+    ) -> Result<Response<Reply>, Status> {
+        info!("Received batches request");
+        let mut stream = request.into_inner();
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            self.prometheus_client.send_batch(batch.into_metrics()).await.map_err(|e| {
+                Status::internal(format!("Failed to send metrics: {}", e))
+            })?;
+        }
+        Ok(Response::new(Reply::default()))
+    }
+    async fn send_series(
+        &self,
+        request: Request<Streaming<SeriesMessage>>,
+    ) -> Result<Response<Reply>, Status> {
+        info!("Received series request");
+        // label sets are defined once per stream
+        let mut table = SeriesTable::default();
+        let mut stream = request.into_inner();
+        while let Some(message) = stream.next().await {
+            let metrics_list = table.decode(message?).map_err(|id| {
+                Status::invalid_argument(format!("Unknown series id {}", id))
+            })?;
+            self.prometheus_client.send_batch(metrics_list).await.map_err(|e| {
+                Status::internal(format!("Failed to send metrics: {}", e))
+            })?;
+        }
+        Ok(Response::new(Reply::default()))
+    }
+    type SendAckedStream = ReceiverStream<Result<Ack, Status>>;
+    async fn send_acked(
+        &self,
+        request: Request<Streaming<SequencedBatch>>,
+    ) -> Result<Response<Self::SendAckedStream>, Status> {
+        info!("Received acked batches request");
+        let mut stream = request.into_inner();
+        let (tx, rx) = mpsc::channel(ACKS);
+        let prometheus_client = self.prometheus_client.clone();
+        tokio::spawn(async move {
+            while let Some(sequenced) = stream.next().await {
+                let sequenced = match sequenced {
+                    Ok(sequenced) => sequenced,
+                    Err(e) => {
+                        error!("Acked batches stream failed: {}", e);
+                        return;
+                    },
+                };
+                let metrics_list = sequenced.batch.map(|b| b.into_metrics()).unwrap_or_default();
+                // without an ack the sender sends the batch again
+                let ack = match prometheus_client.send_acked(metrics_list).await {
+                    Ok(errors) => Ok(Ack{ sequence: sequenced.sequence, error: errors.join("; ") }),
+                    Err(e) => Err(Status::internal(format!("Failed to send metrics: {}", e))),
+                };
+                let failed = ack.is_err();
+                if tx.send(ack).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
     async fn register_metrics(
         &self,
         request: Request<CollectorMetrics>,
@@ -59,4 +132,88 @@ impl CollectorServer for GrpcServer {
         })?;
         Ok(Response::new(Reply::default()))
     }
-}
\ No newline at end of file
+    async fn unregister_metrics(
+        &self,
+        request: Request<CollectorMetrics>,
+    ) -> Result<Response<Reply>, Status> {
+        info!("Received unregister request");
+        let metrics = request.into_inner();
+        self.prometheus_client.unregister(metrics).await.map_err(|e| {
+            Status::internal(format!("Failed to unregister metrics: {}", e))
+        })?;
+        Ok(Response::new(Reply::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::prometheus::prometheus::Prometheus;
+    use collector_protocol::collector::collector::collector_server_client::CollectorServerClient;
+    use collector_protocol::metrics::metrics::batches;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn free_address() -> String{
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string()
+    }
+
+    async fn scrape(address: &str) -> String{
+        for _ in 0..50{
+            if let Ok(mut stream) = tokio::net::TcpStream::connect(address).await{
+                stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+                return response;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("web server {} not reachable", address);
+    }
+
+    #[tokio::test]
+    async fn test_send_acked(){
+        let grpc_address = free_address();
+        let prometheus_address = free_address();
+        let prometheus = Prometheus::new(prometheus_address.clone());
+        let server = GrpcServer::new(grpc_address.clone(), prometheus.client());
+        tokio::spawn(async move { prometheus.web_server().await });
+        tokio::spawn(async move { server.run().await });
+
+        let mut client = None;
+        for _ in 0..50{
+            if let Ok(c) = CollectorServerClient::connect(format!("http://{}", grpc_address)).await{
+                client = Some(c);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let mut client = client.unwrap();
+
+        // the first batch registers the series, the second reuses in_octets
+        // with different labels next to a series not registered yet
+        let first = CollectorMetrics::default().with_label("host", "node1").with_metric("in_octets", 10);
+        let mismatch = CollectorMetrics::default().with_label("interface", "et-0/0/0")
+            .with_metric("in_octets", 20).with_metric("out_octets", 30);
+        let unregistered = CollectorMetrics::default().with_label("host", "node1").with_metric("in_errors", 40);
+        let requests: Vec<SequencedBatch> = [vec![first], vec![mismatch, unregistered]].into_iter().enumerate()
+            .map(|(sequence, metrics)| SequencedBatch{
+                sequence: sequence as u64,
+                batch: batches(metrics).into_iter().next(),
+            }).collect();
+        let mut acks = client.send_acked(tokio_stream::iter(requests)).await.unwrap().into_inner();
+
+        let ack = acks.next().await.unwrap().unwrap();
+        assert_eq!(ack.sequence, 0);
+        assert!(ack.error.is_empty());
+        let ack = acks.next().await.unwrap().unwrap();
+        assert_eq!(ack.sequence, 1);
+        assert!(ack.error.contains("in_octets"), "{}", ack.error);
+
+        let scraped = scrape(&prometheus_address).await;
+        assert!(scraped.contains("in_octets{host=\"node1\"} 10"), "{}", scraped);
+        assert!(scraped.contains("in_errors{host=\"node1\"} 40"), "{}", scraped);
+        // nothing of the rejected series is set
+        assert!(!scraped.contains("out_octets{"), "{}", scraped);
+        assert!(!scraped.contains(" 20\n"), "{}", scraped);
+    }
+}
@@ -0,0 +1,3 @@
+#![allow(clippy::module_inception)]
+pub mod grpc_server;
+pub mod prometheus;
@@ -1,13 +1,9 @@
-use crate::grpc_server::grpc_server::GrpcServer;
+use collector_server::grpc_server::grpc_server::GrpcServer;
 use clap::Parser;
 use log::info;
-use prometheus::prometheus::Prometheus;
+use collector_server::prometheus::prometheus::Prometheus;
 use tokio::signal;
 
-pub mod grpc_server;
-pub mod collector;
-pub mod prometheus;
-
 #[derive(Parser)]
 pub struct Args{
     #[clap(short, long, default_value = "0.0.0.0:50055")]
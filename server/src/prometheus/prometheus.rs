@@ -1,11 +1,17 @@
-use std::{collections::HashMap, pin::Pin, sync::Arc};
+use std::{collections::{HashMap, HashSet}, pin::Pin, sync::Arc, time::{Duration, Instant}};
 use actix_web::{get, App, HttpServer, Responder};
-use actix_web_prom::PrometheusMetricsBuilder;
-use log::info;
-use prometheus::{GaugeVec, Registry};
-use tokio::sync::RwLock;
+use actix_web_prom::{PrometheusMetrics, PrometheusMetricsBuilder};
+use log::{error, info, warn};
+use prometheus::{core::Collector, GaugeVec, Registry};
+use tokio::sync::{oneshot, RwLock};
+use tokio::task::JoinHandle;
 
-use crate::collector::collector::CollectorMetrics;
+use collector_protocol::collector::collector::CollectorMetrics;
+use collector_protocol::names::names::{label_name, metric_name};
+
+// how long samples of an unregistered series are dropped, long enough for
+// the samples queued by the clients before they unregistered it
+const UNREGISTERED_TTL: Duration = Duration::from_secs(60);
 
 #[get("/")]
 async fn index() -> impl Responder {
@@ -43,109 +49,231 @@ impl Prometheus{
         .build()
         .unwrap();
 
-        let mut gauge_map: Arc<HashMap<String, GaugeVec>> = Arc::new(HashMap::new());
+        // the web server shares the registry, gauges registered later are
+        // exported without restarting it
+        let mut gauge_map: HashMap<String, GaugeVec> = HashMap::new();
+        let mut names = Names::default();
+        // series removed by Unregister with the time they were removed,
+        // samples still on their way to the collector don't bring them back
+        // until they are registered again or UNREGISTERED_TTL passed
+        let mut unregistered: HashMap<(String, Vec<String>), Instant> = HashMap::new();
         let registry = Registry::new();
+        prometheus.registry = registry.clone();
         let rx = self.rx.clone();
-        let mut web_server_handle = None;
+        let mut web_server_handle: Option<JoinHandle<anyhow::Result<()>>> = None;
 
         while let Some(command) = rx.write().await.recv().await{
-            match command{
-                WebServerCommand::Start => {
-                    info!("Starting web server");
-                    let prometheus = prometheus.clone();
-                    let address = self.address.clone();
-                    let handle: tokio::task::JoinHandle<Result<(), anyhow::Error>> = tokio::spawn(async move {
-                        let server = HttpServer::new(move || {
-                            App::new()
-                            .wrap(prometheus.clone())
-                            .service(index)
-                        })
-                        .bind(address.clone())?
-                        .shutdown_timeout(1);
-                        server.run().await?;
-                        Ok(())
-                    });
-                    web_server_handle = Some(handle);
-                },
+            // whether the web server is to be started, it starts with the
+            // first registered gauge
+            let serve = match command{
+                WebServerCommand::Start => true,
                 WebServerCommand::Stop => {
                     info!("Stopping web server");
-                    if let Some(ref web_server_handle) = web_server_handle{
+                    if let Some(web_server_handle) = web_server_handle.take(){
                         web_server_handle.abort();
                     }
+                    false
                 },
-                WebServerCommand::Register(collector_metrics) => {
+                WebServerCommand::Register(collector_metrics, reply) => {
                     info!("Registering metrics");
-                    let gm = setup_metrics(collector_metrics);
-                    let mut new_gauge_map = HashMap::new();
-                    for (k, v) in &gm{
-                        if !gauge_map.contains_key(k){
-                            match registry.register(Box::new(v.clone())){
-                                Ok(_) => {},
-                                Err(e) => {
-                                    if e.to_string().contains("Duplicate metrics collector registration attempted"){
-                                        continue;
-                                    }
-                                    info!("Failed to register metric:{} {}", k, e);
-                                }
-                            }
-                            new_gauge_map.insert(k.clone(), v.clone());
-                        }
+                    let collector_metrics = names.sanitize(collector_metrics);
+                    let vals = sorted_label_values(&collector_metrics);
+                    for k in collector_metrics.metrics.keys(){
+                        unregistered.remove(&(k.clone(), vals.clone()));
                     }
-                    if new_gauge_map.len() > 0{
-                        for (k,v) in gauge_map.iter(){
-                            new_gauge_map.insert(k.clone(), v.clone());
+                    match register_gauges(&mut gauge_map, &registry, &collector_metrics){
+                        Ok(registered) => {
+                            let _ = reply.send(Ok(()));
+                            registered
+                        },
+                        Err(e) => {
+                            error!("Failed to register metrics: {}", e);
+                            let _ = reply.send(Err(e));
+                            false
+                        },
+                    }
+                },
+                WebServerCommand::Unregister(collector_metrics) => {
+                    info!("Unregistering metrics");
+                    let collector_metrics = names.sanitize(collector_metrics);
+                    unregistered.retain(|_, removed| removed.elapsed() < UNREGISTERED_TTL);
+                    let vals = sorted_label_values(&collector_metrics);
+                    let val_refs: Vec<&str> = vals.iter().map(|s| s.as_str()).collect();
+                    for k in collector_metrics.metrics.keys(){
+                        if let Some(gauge) = gauge_map.get(k){
+                            if let Err(e) = gauge.remove_label_values(&val_refs){
+                                info!("Failed to remove series for metric:{} {}", k, e);
+                            }
                         }
-                        gauge_map = Arc::new(new_gauge_map);
-                        prometheus.registry = registry.clone();
-                        self.client.stop().await?;
-                        self.client.start().await?;
+                        unregistered.insert((k.clone(), vals.clone()), Instant::now());
                     }
+                    false
                 },
                 WebServerCommand::SendMetrics(metrics) => {
-                    let mut vals = Vec::new();
-                    let mut sorted_list = metrics.labels.iter().collect::<Vec<_>>();
-                    sorted_list.sort_by(|a, b| a.0.cmp(b.0));
-
-                    for (_, label_value) in sorted_list {
-                        vals.push(label_value.clone());
+                    let metrics = without_unregistered(names.sanitize(metrics), &unregistered);
+                    match set_gauges(&mut gauge_map, &registry, &metrics){
+                        Ok(registered) => registered,
+                        Err(e) => {
+                            error!("Failed to set metrics: {}", e);
+                            false
+                        },
                     }
-
-                    let vals: Vec<&str> = vals.iter().map(|s| s.as_str()).collect();
-                    let slice_vals = vals.as_slice();
-                    let mut not_found = false;
-                    for (k, v) in metrics.clone().metrics {
-                        if let Some(gauge) = gauge_map.get(&k){
-                            Pin::new(gauge).with_label_values(&slice_vals).set(v as f64);
-                        } else {
-                            not_found = true;
-                            
-                            info!("Metric not found: {}", k);
+                },
+                WebServerCommand::SendBatch(metrics_list) => {
+                    let mut registered = false;
+                    for metrics in metrics_list{
+                        let metrics = without_unregistered(names.sanitize(metrics), &unregistered);
+                        match set_gauges(&mut gauge_map, &registry, &metrics){
+                            Ok(r) => registered |= r,
+                            Err(e) => error!("Failed to set metrics: {}", e),
                         }
                     }
-                    if not_found{
-                        self.client.register(metrics.clone()).await?;
+                    registered
+                },
+                WebServerCommand::SendAcked(metrics_list, reply) => {
+                    let mut registered = false;
+                    let mut errors = Vec::new();
+                    for metrics in metrics_list{
+                        let metrics = without_unregistered(names.sanitize(metrics), &unregistered);
+                        match set_gauges(&mut gauge_map, &registry, &metrics){
+                            Ok(r) => registered |= r,
+                            Err(e) => errors.push(e.to_string()),
+                        }
                     }
+                    let _ = reply.send(errors);
+                    registered
                 },
+            };
+            if serve && web_server_handle.is_none(){
+                web_server_handle = Some(self.start_web_server(prometheus.clone()));
             }
         }
+        Ok(())
+    }
 
-        let server = HttpServer::new(move || {
-            App::new()
-            .wrap(prometheus.clone())
-            .service(index)
+    fn start_web_server(&self, prometheus: PrometheusMetrics) -> JoinHandle<anyhow::Result<()>>{
+        info!("Starting web server");
+        let address = self.address.clone();
+        tokio::spawn(async move {
+            let server = HttpServer::new(move || {
+                App::new()
+                .wrap(prometheus.clone())
+                .service(index)
+            })
+            .bind(address.clone())?
+            .shutdown_timeout(1);
+            server.run().await?;
+            Ok(())
         })
-        .bind(self.address.clone())?
-        .shutdown_timeout(1);
-        
-        server.run().await?;
+    }
+}
 
-        
+// Names are made valid with the rules the senders use, e.g. the - of Junos
+// key paths becomes _, so the gauges can be created. Distinct names mapped
+// to the same name share a gauge, which is warned about once per name.
+#[derive(Default)]
+struct Names{
+    // the names mapped to a sanitized name
+    originals: HashMap<String, HashSet<String>>,
+}
 
-        Ok(())
+impl Names{
+    fn sanitize(&mut self, metrics: CollectorMetrics) -> CollectorMetrics{
+        CollectorMetrics{
+            namespace: metrics.namespace,
+            labels: metrics.labels.into_iter().map(|(k, v)| (self.sanitize_name(k, label_name), v)).collect(),
+            metrics: metrics.metrics.into_iter().map(|(k, v)| (self.sanitize_name(k, metric_name), v)).collect(),
+        }
+    }
+
+    fn sanitize_name(&mut self, name: String, valid_name: fn(&str) -> String) -> String{
+        let sanitized = valid_name(&name);
+        let originals = self.originals.entry(sanitized.clone()).or_default();
+        if !originals.contains(&name){
+            if !originals.is_empty(){
+                warn!("{} and {:?} are both exported as {}", name, originals, sanitized);
+            }
+            originals.insert(name);
+        }
+        sanitized
+    }
+}
+
+// registers the gauges of the metrics which have none yet, returns whether
+// a gauge was added. Fails without registering any gauge if one of them
+// can't be created.
+fn register_gauges(gauge_map: &mut HashMap<String, GaugeVec>, registry: &Registry, metrics: &CollectorMetrics) -> anyhow::Result<bool>{
+    let mut registered = false;
+    for (k, v) in setup_metrics(metrics)?{
+        if gauge_map.contains_key(&k){
+            continue;
+        }
+        match registry.register(Box::new(v.clone())){
+            Ok(_) => {},
+            Err(e) => {
+                if e.to_string().contains("Duplicate metrics collector registration attempted"){
+                    continue;
+                }
+                info!("Failed to register metric:{} {}", k, e);
+            }
+        }
+        gauge_map.insert(k, v);
+        registered = true;
+    }
+    Ok(registered)
+}
+
+// sets the gauges of the metrics, the missing ones are registered first.
+// Returns whether a gauge was registered. Fails without setting any gauge if
+// the labels differ from the labels a gauge was registered with or a gauge
+// could not be registered.
+fn set_gauges(gauge_map: &mut HashMap<String, GaugeVec>, registry: &Registry, metrics: &CollectorMetrics) -> anyhow::Result<bool>{
+    let mut names: Vec<&String> = metrics.labels.keys().collect();
+    names.sort();
+    for k in metrics.metrics.keys(){
+        if let Some(gauge) = gauge_map.get(k){
+            let registered = &gauge.desc()[0].variable_labels;
+            if !registered.iter().eq(names.iter().copied()){
+                anyhow::bail!("metric {} has labels {:?}, registered with {:?}", k, names, registered);
+            }
+        }
+    }
+    let mut registered = false;
+    if metrics.metrics.keys().any(|k| !gauge_map.contains_key(k)){
+        info!("Metrics not found, registering");
+        registered = register_gauges(gauge_map, registry, metrics)?;
+        if let Some(k) = metrics.metrics.keys().find(|k| !gauge_map.contains_key(*k)){
+            anyhow::bail!("metric {} could not be registered", k);
+        }
+    }
+    let vals = sorted_label_values(metrics);
+    let vals: Vec<&str> = vals.iter().map(|s| s.as_str()).collect();
+    let slice_vals = vals.as_slice();
+    for (k, v) in &metrics.metrics {
+        Pin::new(&gauge_map[k]).with_label_values(slice_vals).set(*v as f64);
+    }
+    Ok(registered)
+}
+
+fn without_unregistered(mut metrics: CollectorMetrics, unregistered: &HashMap<(String, Vec<String>), Instant>) -> CollectorMetrics{
+    if unregistered.is_empty(){
+        return metrics;
     }
+    let vals = sorted_label_values(&metrics);
+    metrics.metrics.retain(|k, _| unregistered.get(&(k.clone(), vals.clone())).is_none_or(|removed| removed.elapsed() >= UNREGISTERED_TTL));
+    metrics
+}
+
+// label values ordered by label name, matching the label order of the gauges
+fn sorted_label_values(metrics: &CollectorMetrics) -> Vec<String> {
+    let mut sorted_list = metrics.labels.iter().collect::<Vec<_>>();
+    sorted_list.sort_by(|a, b| a.0.cmp(b.0));
+    sorted_list.into_iter().map(|(_, label_value)| label_value.clone()).collect()
 }
 
-fn setup_metrics(metrics: CollectorMetrics) -> HashMap<String, GaugeVec> {
+// a gauge per metric with the label names in sorted order, fails on names
+// the prometheus crate rejects, e.g. empty ones
+fn setup_metrics(metrics: &CollectorMetrics) -> anyhow::Result<HashMap<String, GaugeVec>> {
     let mut gauge_map = HashMap::new();
     let mut vals = Vec::with_capacity(metrics.labels.len());
 
@@ -168,10 +296,10 @@ fn setup_metrics(metrics: CollectorMetrics) -> HashMap<String, GaugeVec> {
                 opts
             },
             slice_vals,
-        ).unwrap();
+        ).map_err(|e| anyhow::anyhow!("invalid metric {}: {}", k, e))?;
         gauge_map.insert(k.clone(), gauge);
     }
-    gauge_map
+    Ok(gauge_map)
 }
 
 
@@ -179,7 +307,13 @@ enum WebServerCommand{
     Start,
     Stop,
     SendMetrics(CollectorMetrics),
-    Register(CollectorMetrics)
+    // the series of a batch, handled with a single command
+    SendBatch(Vec<CollectorMetrics>),
+    // like SendBatch, replies with the errors of the rejected series
+    SendAcked(Vec<CollectorMetrics>, oneshot::Sender<Vec<String>>),
+    // replies whether the gauges could be registered
+    Register(CollectorMetrics, oneshot::Sender<anyhow::Result<()>>),
+    Unregister(CollectorMetrics),
 }
 
 #[derive(Clone)]
@@ -195,7 +329,13 @@ impl Client{
     }
 
     pub async fn register(&self, metrics: CollectorMetrics) -> anyhow::Result<()>{
-        self.tx.send(WebServerCommand::Register(metrics)).await?;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx.send(WebServerCommand::Register(metrics, reply_tx)).await?;
+        reply_rx.await?
+    }
+
+    pub async fn unregister(&self, metrics: CollectorMetrics) -> anyhow::Result<()>{
+        self.tx.send(WebServerCommand::Unregister(metrics)).await?;
         Ok(())
     }
 
@@ -204,6 +344,19 @@ impl Client{
         Ok(())
     }
 
+    pub async fn send_batch(&self, metrics_list: Vec<CollectorMetrics>) -> anyhow::Result<()>{
+        self.tx.send(WebServerCommand::SendBatch(metrics_list)).await?;
+        Ok(())
+    }
+
+    // the errors of the series which were rejected, empty if the batch was
+    // applied completely
+    pub async fn send_acked(&self, metrics_list: Vec<CollectorMetrics>) -> anyhow::Result<Vec<String>>{
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx.send(WebServerCommand::SendAcked(metrics_list, reply_tx)).await?;
+        Ok(reply_rx.await?)
+    }
+
     pub async fn start(&self) -> anyhow::Result<()>{
         self.tx.send(WebServerCommand::Start).await?;
         Ok(())
@@ -213,4 +366,46 @@ impl Client{
         self.tx.send(WebServerCommand::Stop).await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_sanitize(){
+        let mut names = Names::default();
+        let metrics = names.sanitize(CollectorMetrics::default()
+            .with_label("interface-name", "et-0/0/0")
+            .with_label("oc:name", "et-0/0/0")
+            .with_metric("in-octets", 1)
+            .with_metric("5min-load", 2)
+            .with_metric("junos:in-pkts", 3));
+        // label names can't hold a :, metric names can
+        let mut labels: Vec<&String> = metrics.labels.keys().collect();
+        labels.sort();
+        assert_eq!(labels, ["interface_name", "oc_name"]);
+        let mut keys: Vec<&String> = metrics.metrics.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["_5min_load", "in_octets", "junos:in_pkts"]);
+        assert_eq!(metrics.labels["interface_name"], "et-0/0/0");
+
+        // in_octets is taken by in-octets
+        names.sanitize(CollectorMetrics::default().with_metric("in_octets", 3));
+        assert_eq!(names.originals["in_octets"].len(), 2);
+        names.sanitize(CollectorMetrics::default().with_metric("in-octets", 4));
+        assert_eq!(names.originals["in_octets"].len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_register_invalid_name(){
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let prometheus = Prometheus::new(address);
+        let client = prometheus.client();
+        tokio::spawn(async move{ prometheus.web_server().await });
+        // the request fails instead of the server
+        let metrics = CollectorMetrics::default().with_label("interface_name", "et-0/0/0");
+        assert!(client.register(metrics.clone().with_metric("", 1)).await.is_err());
+        client.register(metrics.with_metric("in_octets", 1)).await.unwrap();
+    }
+}